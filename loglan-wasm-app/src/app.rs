@@ -2,7 +2,7 @@ use camxes_rs::grammars::LOGLAN_GRAMMAR;
 use camxes_rs::peg::grammar::Peg;
 use gloo_timers::future::TimeoutFuture;
 use log::{error, info};
-use std::cell::RefCell;
+use std::sync::OnceLock;
 use wasm_bindgen_futures::spawn_local;
 use web_sys::{self, HtmlSelectElement}; // Removed unused HtmlTextAreaElement
 use yew::prelude::*;
@@ -14,16 +14,13 @@ enum OutputFormat {
     Json,
 }
 
-// Use a simpler RefCell for the Peg instance, initialization handled in the component
-thread_local! {
-    static LOGLAN_PEG: RefCell<Option<Result<Peg, String>>> = RefCell::new(None);
-}
+// The grammar is immutable and `Sync`, so it is built once and shared by reference.
+static LOGLAN_PEG: OnceLock<Result<Peg, String>> = OnceLock::new();
 
 // Function to get or initialize the PEG parser
-fn get_or_init_peg() -> Result<Peg, String> {
-    LOGLAN_PEG.with(|peg_cell| {
-        let mut peg_opt_res = peg_cell.borrow_mut();
-        if peg_opt_res.is_none() {
+fn get_or_init_peg() -> Result<&'static Peg, String> {
+    LOGLAN_PEG
+        .get_or_init(|| {
             info!("Initializing Loglan PEG parser...");
             let (start, grammar) = LOGLAN_GRAMMAR;
             let result = Peg::new(start, grammar).map_err(|e| {
@@ -34,15 +31,10 @@ fn get_or_init_peg() -> Result<Peg, String> {
             if result.is_ok() {
                 info!("Loglan PEG parser initialized successfully.");
             }
-            *peg_opt_res = Some(result);
-        }
-
-        // Clone the result inside the Option
-        match peg_opt_res.as_ref().unwrap() {
-            Ok(peg) => Ok(peg.clone()), // Clone the Peg if Ok
-            Err(e) => Err(e.clone()),   // Clone the error string if Err
-        }
-    })
+            result
+        })
+        .as_ref()
+        .map_err(|e| e.clone())
 }
 
 #[function_component(App)]
@@ -81,7 +73,10 @@ pub fn app() -> Html {
             let input = (*input_text_handle).clone();
             match get_or_init_peg() {
                 Ok(peg) => {
-                    info!("Parsing input: '{}' with format: {:?}", input, *output_format_handle);
+                    info!(
+                        "Parsing input: '{}' with format: {:?}",
+                        input, *output_format_handle
+                    );
                     let result_str = match *output_format_handle {
                         OutputFormat::Debug => {
                             let result = peg.parse(&input);
@@ -114,9 +109,7 @@ pub fn app() -> Html {
         Callback::from(move |_| {
             let result_text = (*parse_result_handle).clone(); // Clone AttrValue
             let button_text_handle = copy_button_text_handle.clone();
-            if let Some(clipboard) = web_sys::window()
-                .and_then(|win| Some(win.navigator().clipboard()))
-            {
+            if let Some(clipboard) = web_sys::window().map(|win| win.navigator().clipboard()) {
                 let promise = clipboard.write_text(&result_text);
                 spawn_local(async move {
                     match wasm_bindgen_futures::JsFuture::from(promise).await {
@@ -139,7 +132,7 @@ pub fn app() -> Html {
             } else {
                 error!("Clipboard API not available.");
                 button_text_handle.set(AttrValue::from("No API"));
-                 // Reset button text after a delay
+                // Reset button text after a delay
                 let button_text_handle_clone = button_text_handle.clone();
                 spawn_local(async move {
                     TimeoutFuture::new(1500).await;
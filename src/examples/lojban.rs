@@ -0,0 +1,101 @@
+use camxes_rs::grammars::LOJBAN_GRAMMAR;
+use camxes_rs::peg::diagnostic::Diagnostic;
+use camxes_rs::peg::grammar::Peg;
+
+fn main() {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    env_logger::builder().init();
+
+    let (start, grammar) = LOJBAN_GRAMMAR;
+    let p = Peg::new(start, grammar).unwrap();
+
+    // TSV path: first arg, or default examples/lojban_corpus.tsv next to Cargo.toml
+    let tsv_path = env::args()
+        .nth(1)
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| {
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("examples")
+                .join("lojban_corpus.tsv")
+        });
+
+    let tsv_content = match fs::read_to_string(&tsv_path) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", tsv_path.display(), e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut passed = 0usize;
+    let mut failed = 0usize;
+
+    for (line_no, line) in tsv_content.lines().enumerate() {
+        let line_no = line_no + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut cols = line.splitn(2, '\t');
+        let text = match cols.next() {
+            Some(s) => s.trim(),
+            None => continue,
+        };
+        let expected = match cols.next().map(str::trim) {
+            Some("ok") => true,
+            Some("error") => false,
+            Some("expected") if text == "text" => continue, // header
+            Some(other) => {
+                eprintln!(
+                    "{}:{}: expected column must be ok or error, got {:?}",
+                    tsv_path.display(),
+                    line_no,
+                    other
+                );
+                failed += 1;
+                continue;
+            }
+            None => {
+                eprintln!(
+                    "{}:{}: missing expected column",
+                    tsv_path.display(),
+                    line_no
+                );
+                failed += 1;
+                continue;
+            }
+        };
+
+        let result = p.parse(text);
+        if result.is_ok() == expected {
+            println!("PASS {}:{}  {}", tsv_path.display(), line_no, text);
+            passed += 1;
+            continue;
+        }
+
+        failed += 1;
+        match result.error() {
+            Some(e) => println!(
+                "FAIL {}:{}  {}  parse error:\n{}",
+                tsv_path.display(),
+                line_no,
+                text,
+                Diagnostic::new(text).render_parse_error(e)
+            ),
+            None => println!(
+                "FAIL {}:{}  {}  parsed, but should be rejected",
+                tsv_path.display(),
+                line_no,
+                text
+            ),
+        }
+    }
+
+    println!("\n--- {} passed, {} failed ---", passed, failed);
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
@@ -1,4 +1,4 @@
-//! Publicly accessible grammar constants.
+//! Publicly accessible grammar constants: [`LOGLAN_GRAMMAR`] and [`LOJBAN_GRAMMAR`].
 //!
 //! For long-running apps, build the parser once and reuse: `let peg = Peg::new(LOGLAN_GRAMMAR.0, LOGLAN_GRAMMAR.1).unwrap();`
 
@@ -999,5 +999,10 @@ uttF <- (uttC (I freemod? uttC)*)
 utterance0 <- (!GE ((ICAUTT freemod? uttF)/(!OptPause freemod Period? utterance0)/(!OptPause freemod Period?)/(uttF IGE utterance0)/uttF/(I freemod? uttF?)/(I freemod? Period?)) (&I utterance0)?)
 
 utterance <- (&(PhoneticUtterance End) (!GE ((ICAUTT freemod? uttF (&I utterance)? End)/(!OptPause freemod Period? utterance)/(!OptPause freemod Period? (&I utterance)? End)/(uttF IGE utterance)/(I freemod? Period? (&I utterance)? End)/(uttF (&I utterance)? End)/(I freemod? uttF (&I utterance)? End))))
-"#
+"#,
 );
+
+/// The Lojban grammar (morphology and syntax), after the camxes PEG grammar. The grammar file
+/// notes where it differs from camxes.
+/// Format: (start_rule, grammar_string)
+pub const LOJBAN_GRAMMAR: (&str, &str) = ("text", include_str!("grammars/lojban.peg"));
@@ -24,4 +24,10 @@
 //!
 
 pub mod grammars;
-pub mod peg;
\ No newline at end of file
+pub mod loglan;
+pub mod lojban;
+pub mod peg;
+/// The README's examples, compiled and run as doctests.
+#[cfg(doctest)]
+#[doc = include_str!("../README.md")]
+pub struct ReadmeDoctests;
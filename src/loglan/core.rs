@@ -0,0 +1,285 @@
+use super::types::{Syllable, Word, WordClass};
+use crate::grammars::LOGLAN_GRAMMAR;
+use crate::peg::grammar::{ParseContext, Peg};
+use crate::peg::parsing::{ParseError, ParseNode, Span};
+use once_cell::sync::Lazy;
+
+/// Built on first use.
+static LOGLAN: Lazy<Peg> =
+    Lazy::new(|| Peg::new(LOGLAN_GRAMMAR.0, LOGLAN_GRAMMAR.1).expect("LOGLAN_GRAMMAR is valid"));
+
+/// Lexemes of little words, each with the grammar rule that matches a bare word of it, tried
+/// in order. Most rules carry the lexeme's name; the others would also want the words that
+/// follow (`LIU` a name, `LIE` the alien text) or match only parts of longer words (`NI0`).
+const LEXEMES: &[(&str, &str)] = &[
+    ("A", "A"),
+    ("ZE", "ZE"),
+    ("CA", "CA"),
+    ("I", "I"),
+    ("KA", "KA"),
+    ("KI", "KI"),
+    ("NI", "NI"),
+    ("CI", "CI"),
+    ("TAI", "TAI"),
+    ("DA", "DA"),
+    ("PA", "PAWORD"),
+    ("GA", "GA"),
+    ("ZI", "ZI"),
+    ("LE", "LE"),
+    ("LIO", "LIO"),
+    ("LAU", "LAU"),
+    ("LOU", "LOU"),
+    ("LUA", "LUA"),
+    ("LUO", "LUO"),
+    ("LI", "LIWORD"),
+    ("LU", "LUWORD"),
+    ("LAO", "LAOalien"),
+    ("LIE", "LIEalien"),
+    ("LIU", "LIU0"),
+    ("SUE", "SUEalien"),
+    ("SUE", "SAOalien"),
+    ("CUI", "CUI"),
+    ("GE", "GE"),
+    ("GEU", "GEU"),
+    ("GI", "GI"),
+    ("GO", "GO"),
+    ("GU", "GU"),
+    ("GUI", "GUI"),
+    ("GUO", "GUO"),
+    ("GUU", "GUU"),
+    ("GUE", "GUE"),
+    ("JE", "JE"),
+    ("JUE", "JUE"),
+    ("JI", "JI"),
+    ("JIO", "JIO"),
+    ("DIO", "DIO"),
+    ("LAE", "LAE"),
+    ("ME", "ME"),
+    ("MEU", "MEU"),
+    ("NU", "NU"),
+    ("PO", "PO"),
+    ("DIE", "DIE"),
+    ("HOI", "HOI"),
+    ("JO", "JO"),
+    ("KIE", "KIE"),
+    ("KIU", "KIU"),
+    ("SOI", "SOI"),
+    ("UI", "UI"),
+    ("HUE", "HUE"),
+    ("BI", "BI"),
+    ("NO", "NOWORD"),
+    ("PREDA", "LWPREDA0"),
+    ("NOI", "NOI0"),
+    ("NI", "NI0"),
+];
+
+/// Rules matching one syllable; the walk stops at the first of these it meets.
+const SYLLABLES: &[&str] = &[
+    "Syllable",
+    "DefaultStressedSyllable",
+    "StressedSyllable",
+    "BorrowingSyllable",
+    "VowelFinal",
+    "NameEndSyllable",
+    "MaybePauseSyllable",
+];
+
+/// Splits Loglan text into words, using the grammar's `PhoneticUtterance` rule. Little words
+/// written together (`lepo`) come back one by one. Fails where the text is not made of Loglan
+/// words, e.g. a name without a marker or pause before it.
+pub fn classify(text: &str) -> Result<Vec<Word>, ParseError> {
+    let result = LOGLAN.parse_rule("PhoneticUtterance", text);
+    let nodes = result.as_result().map_err(Clone::clone)?;
+    let mut analysis = Analysis {
+        input: text,
+        ctx: LOGLAN.context(),
+        words: vec![],
+    };
+    for node in nodes {
+        analysis.visit(node);
+    }
+    Ok(analysis.words)
+}
+
+struct Analysis<'a> {
+    input: &'a str,
+    ctx: ParseContext<'static>,
+    words: Vec<Word>,
+}
+
+impl Analysis<'_> {
+    /// Finds the words below a node of the word-level rules.
+    fn visit(&mut self, node: &ParseNode) {
+        let Some(name) = node.name() else {
+            return;
+        };
+        match name {
+            "Complex" | "CcvNoY" => {
+                let class = if is_primitive(node.text(self.input)) {
+                    WordClass::Primitive
+                } else {
+                    WordClass::Complex
+                };
+                self.push(node.span(), class, syllables(node, self.input));
+            }
+            "Borrowing" => self.push(
+                node.span(),
+                WordClass::Borrowing,
+                syllables(node, self.input),
+            ),
+            "Cmapua" => self.little_words(node),
+            "PRENAME" => self.push(node.span(), WordClass::Name, syllables(node, self.input)),
+            "AlienText" => {
+                let span = trim(self.input, node.span(), |c| c == ',' || c.is_whitespace());
+                self.push(span, WordClass::AlienText, vec![]);
+            }
+            "LiQuote" => self.quote(node),
+            // Markers before names (`la`, `hoi`, ...) and alien text (`lie`, `sao`, ...)
+            _ if name.ends_with("name") || name.ends_with("name2") || name.ends_with("alien") => {
+                self.little_word(node.span());
+            }
+            _ => {
+                for child in node.children() {
+                    self.visit(child);
+                }
+            }
+        }
+    }
+
+    /// A `Cmapua` is one or more little words; its units are the child nodes, with any juncture
+    /// kept on the unit before it and text outside the units (a leading `no`) as its own word.
+    fn little_words(&mut self, node: &ParseNode) {
+        let Span(start, end) = node.span();
+        let mut units: Vec<Span> = vec![];
+        let mut at = start;
+        for child in node.children().iter().filter(|c| c.name().is_some()) {
+            let Span(s, e) = child.span();
+            if child.name() == Some("Juncture") {
+                if let Some(last) = units.last_mut() {
+                    last.1 = e;
+                    at = e;
+                    continue;
+                }
+            }
+            if s > at {
+                units.push(Span(at, s));
+            }
+            units.push(Span(s, e));
+            at = e;
+        }
+        if end > at {
+            units.push(Span(at, end));
+        }
+        for unit in units {
+            self.little_word(unit);
+        }
+    }
+
+    /// `li "..." lu` and `kie (...) kiu`: the quoting words, with the quoted words between.
+    fn quote(&mut self, node: &ParseNode) {
+        let Span(start, end) = node.span();
+        let Some(inner) = node
+            .children()
+            .iter()
+            .find(|c| c.name() == Some("PhoneticUtterance"))
+        else {
+            return;
+        };
+        let Span(inner_start, inner_end) = inner.span();
+        let punctuation =
+            |c: char| c == ',' || c == '"' || c == '(' || c == ')' || c.is_whitespace();
+        self.little_word(trim(self.input, Span(start, inner_start), punctuation));
+        self.visit(inner);
+        self.little_word(trim(self.input, Span(inner_end, end), punctuation));
+    }
+
+    fn little_word(&mut self, span: Span) {
+        let text = &self.input[span.0..span.1];
+        let lexeme = LEXEMES
+            .iter()
+            .find(|(_, rule)| self.ctx.parse_rule(rule, text).is_ok())
+            .map(|(lexeme, _)| lexeme.to_string());
+        self.push(span, WordClass::LittleWord, vec![]);
+        if let Some(word) = self.words.last_mut() {
+            word.lexeme = lexeme;
+        }
+    }
+
+    fn push(&mut self, span: Span, class: WordClass, syllables: Vec<Syllable>) {
+        if span.0 >= span.1 {
+            return;
+        }
+        self.words.push(Word {
+            text: self.input[span.0..span.1].to_string(),
+            class,
+            span,
+            lexeme: None,
+            syllables,
+        });
+    }
+}
+
+/// The syllables below a word node. A syllable marked with `'` or `*` carries the stress;
+/// without one, the `DefaultStressedSyllable` of a complex or the first syllable of a
+/// borrowing's tail does.
+fn syllables(node: &ParseNode, input: &str) -> Vec<Syllable> {
+    fn collect(node: &ParseNode, in_tail: bool, found: &mut Vec<(Span, bool)>) {
+        let Some(name) = node.name() else {
+            return;
+        };
+        if SYLLABLES.contains(&name) {
+            let default = name == "DefaultStressedSyllable"
+                || (in_tail && !found.iter().any(|(_, default)| *default));
+            found.push((node.span(), default));
+            return;
+        }
+        let in_tail = in_tail || name.starts_with("BorrowingTail");
+        for child in node.children() {
+            collect(child, in_tail, found);
+        }
+    }
+
+    let mut found = vec![];
+    collect(node, false, &mut found);
+    let text = |Span(s, e): Span| &input[s..e];
+    let marked = found
+        .iter()
+        .any(|&(span, _)| text(span).contains(['\'', '*']));
+    // The grammar only places default stress in predicates; names take the penultimate
+    // syllable, or their only one
+    if !found.iter().any(|(_, default)| *default) {
+        let penultimate = found.len().saturating_sub(2);
+        if let Some((_, default)) = found.get_mut(penultimate) {
+            *default = true;
+        }
+    }
+    found
+        .into_iter()
+        .map(|(span, default)| Syllable {
+            text: text(span).to_string(),
+            span,
+            stressed: if marked {
+                text(span).contains(['\'', '*'])
+            } else {
+                default
+            },
+        })
+        .collect()
+}
+
+/// CCVCV or CVCCV, ignoring junctures.
+fn is_primitive(word: &str) -> bool {
+    let pattern: String = word
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| if "aeiouAEIOU".contains(c) { 'V' } else { 'C' })
+        .collect();
+    pattern == "CCVCV" || pattern == "CVCCV"
+}
+
+fn trim(input: &str, Span(start, end): Span, strip: impl Fn(char) -> bool + Copy) -> Span {
+    let text = &input[start..end];
+    let leading = text.len() - text.trim_start_matches(strip).len();
+    let trailing = text.len() - text.trim_end_matches(strip).len();
+    Span(start + leading, (end - trailing).max(start + leading))
+}
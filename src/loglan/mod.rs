@@ -0,0 +1,35 @@
+//! Word-level analysis of Loglan text with the morphology rules of
+//! [`LOGLAN_GRAMMAR`](crate::grammars::LOGLAN_GRAMMAR): each word with its class, the lexeme
+//! of little words and the syllables of predicates and names.
+//!
+//! ```rust
+//! use camxes_rs::loglan::{classify, WordClass};
+//!
+//! let words = classify("Hoi Djan, mi cluva le sancymao").unwrap();
+//! let summary: Vec<_> = words
+//!     .iter()
+//!     .map(|w| (w.text.as_str(), w.class, w.lexeme.as_deref()))
+//!     .collect();
+//! assert_eq!(
+//!     summary,
+//!     [
+//!         ("Hoi", WordClass::LittleWord, Some("HOI")),
+//!         ("Djan", WordClass::Name, None),
+//!         ("mi", WordClass::LittleWord, Some("DA")),
+//!         ("cluva", WordClass::Primitive, None),
+//!         ("le", WordClass::LittleWord, Some("LE")),
+//!         ("sancymao", WordClass::Complex, None),
+//!     ]
+//! );
+//!
+//! let syllables: Vec<_> = words[3].syllables.iter().map(|s| (s.text.as_str(), s.stressed)).collect();
+//! assert_eq!(syllables, [("clu", true), ("va", false)]);
+//! let syllables: Vec<_> = words[1].syllables.iter().map(|s| (s.text.as_str(), s.stressed)).collect();
+//! assert_eq!(syllables, [("Djan", true)]);
+//! ```
+
+mod core;
+mod types;
+
+pub use self::core::classify;
+pub use self::types::{Syllable, Word, WordClass};
@@ -0,0 +1,42 @@
+use crate::peg::parsing::Span;
+
+/// The morphological class of a Loglan word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WordClass {
+    /// A five-letter predicate word, e.g. `cluva`
+    Primitive,
+    /// A predicate built from affixes, e.g. `sancymao`
+    Complex,
+    /// A predicate borrowed from another language, e.g. `alkoholi`
+    Borrowing,
+    /// A structure word, e.g. `mi`, `le` or `hoi`
+    LittleWord,
+    Name,
+    /// Foreign text quoted by `hoi`, `hue`, `lie`, `lao`, `lio`, `sao` or `sue`
+    AlienText,
+}
+
+/// A syllable of a word, as the grammar divides it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Syllable {
+    pub text: String,
+    /// Byte range in the analysed text
+    pub span: Span,
+    /// Marked with `'` or `*`, or else the syllable that takes the stress by default (the
+    /// penultimate one in names)
+    pub stressed: bool,
+}
+
+/// One word of a Loglan text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Word {
+    /// The word as written
+    pub text: String,
+    pub class: WordClass,
+    /// Byte range in the analysed text
+    pub span: Span,
+    /// For a little word, the grammar rule naming its lexeme, e.g. `HOI`, `LIE` or `DA`
+    pub lexeme: Option<String>,
+    /// The syllables of predicates and names; empty for little words and alien text
+    pub syllables: Vec<Syllable>,
+}
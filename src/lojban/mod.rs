@@ -0,0 +1,3 @@
+//! Lojban-specific tools built on [`LOJBAN_GRAMMAR`](crate::grammars::LOJBAN_GRAMMAR).
+
+pub mod morphology;
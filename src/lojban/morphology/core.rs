@@ -0,0 +1,193 @@
+use super::errors::MorphologyError;
+use super::types::{Rafsi, RafsiKind, RafsiTable};
+use crate::grammars::LOJBAN_GRAMMAR;
+use crate::peg::grammar::{Peg, PegBuilder};
+use crate::peg::parsing::{ParseNode, Span};
+use once_cell::sync::Lazy;
+
+/// Entry rule splitting any text into words; text that is not a Lojban word still makes one.
+const WORDS: &str = "words <- (lojban_word / non_lojban_word / space_char / comma)*";
+
+/// Built on first use; building the whole Lojban grammar takes a moment.
+pub(super) static LOJBAN: Lazy<Peg> = Lazy::new(|| {
+    PegBuilder::new(LOJBAN_GRAMMAR.1)
+        .overlay(WORDS)
+        .build("words")
+        .expect("LOJBAN_GRAMMAR is valid")
+});
+
+/// Splits a brivla into its rafsi and hyphens, in order. A gismu or a fu'ivla comes back as a
+/// single part. Surrounding whitespace is ignored; spans are byte offsets into `word`.
+pub fn decompose(word: &str) -> Result<Vec<Rafsi>, MorphologyError> {
+    let trimmed = word.trim_start();
+    let offset = word.len() - trimmed.len();
+    let trimmed = trimmed.trim_end();
+
+    let result = LOJBAN.parse_rule("brivla", trimmed);
+    let nodes = result
+        .as_result()
+        .map_err(|error| MorphologyError::NotABrivla(error.clone()))?;
+
+    let mut parts = Vec::new();
+    for node in nodes {
+        collect(node, trimmed, &mut parts);
+    }
+    Ok(parts
+        .into_iter()
+        .map(|(Span(start, end), kind)| Rafsi {
+            text: trimmed[start..end].to_string(),
+            kind,
+            span: Span(start + offset, end + offset),
+            gismu: None,
+        })
+        .collect())
+}
+
+/// [`decompose`], filling in [`Rafsi::gismu`] from `table`. A whole gismu maps to itself even
+/// when the table does not list it.
+pub fn decompose_with(word: &str, table: &RafsiTable) -> Result<Vec<Rafsi>, MorphologyError> {
+    let mut parts = decompose(word)?;
+    for part in parts
+        .iter_mut()
+        .filter(|part| part.kind != RafsiKind::Hyphen)
+    {
+        part.gismu = match table.gismu_for(&part.text) {
+            Some(gismu) => Some(gismu.to_string()),
+            None if part.kind == RafsiKind::Gismu => Some(normalize(&part.text)),
+            None => None,
+        };
+    }
+    Ok(parts)
+}
+
+/// Walks the `brivla` tree down to the rules that match a single rafsi, splitting off the
+/// hyphen the `y` and `r`/`n` forms carry.
+fn collect(node: &ParseNode, input: &str, parts: &mut Vec<(Span, RafsiKind)>) {
+    let Some(name) = node.name() else {
+        return;
+    };
+    let Span(start, end) = node.span();
+    let hyphen_at = |hyphens: &[&str]| {
+        node.children()
+            .iter()
+            .find(|child| child.name().is_some_and(|name| hyphens.contains(&name)))
+            .map_or(end, |child| child.span().0)
+    };
+
+    let split = match name {
+        "fuhivla" => {
+            parts.push((node.span(), RafsiKind::Fuhivla));
+            return;
+        }
+        "y_rafsi"
+        | "stressed_y_rafsi"
+        | "brivla_rafsi"
+        | "stressed_brivla_rafsi"
+        | "fuhivla_rafsi"
+        | "stressed_fuhivla_rafsi" => hyphen_at(&["h", "y"]),
+        "CVV_rafsi" | "stressed_CVV_rafsi" => hyphen_at(&["r_hyphen"]),
+        "gismu" | "CVV_final_rafsi" | "short_final_rafsi" | "CVC_rafsi" | "CCV_rafsi"
+        | "stressed_CVC_rafsi" | "stressed_CCV_rafsi" => end,
+        _ => {
+            for child in node.children() {
+                collect(child, input, parts);
+            }
+            return;
+        }
+    };
+
+    parts.push((Span(start, split), kind_of(&input[start..split])));
+    if split < end {
+        parts.push((Span(split, end), RafsiKind::Hyphen));
+    }
+}
+
+/// Classifies a rafsi by its consonant/vowel pattern.
+pub(super) fn kind_of(rafsi: &str) -> RafsiKind {
+    let pattern: String = normalize(rafsi)
+        .chars()
+        .filter_map(|c| match c {
+            '\'' => None,
+            'a' | 'e' | 'i' | 'o' | 'u' => Some('V'),
+            'y' => Some('y'),
+            _ => Some('C'),
+        })
+        .collect();
+    match pattern.as_str() {
+        "CVC" => RafsiKind::Cvc,
+        "CCV" => RafsiKind::Ccv,
+        "CVV" => RafsiKind::Cvv,
+        "CVCC" => RafsiKind::Cvcc,
+        "CCVC" => RafsiKind::Ccvc,
+        "CVCCV" | "CCVCV" => RafsiKind::Gismu,
+        _ => RafsiKind::Fuhivla,
+    }
+}
+
+/// Lowercase, without commas, with `h` written as `'`.
+pub(super) fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|&c| c != ',')
+        .map(|c| match c.to_ascii_lowercase() {
+            'h' => '\'',
+            c => c,
+        })
+        .collect()
+}
+
+impl RafsiTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a gismu and its rafsi; see [`insert`](Self::insert).
+    pub fn with(mut self, gismu: &str, rafsi: &[&str]) -> Self {
+        self.insert(gismu, rafsi);
+        self
+    }
+
+    /// Adds a gismu and its short rafsi. Its four-letter form need not be listed.
+    pub fn insert(&mut self, gismu: &str, rafsi: &[&str]) {
+        let gismu = normalize(gismu);
+        for r in rafsi {
+            let r = normalize(r);
+            // A rafsi belongs to one gismu; listing it again moves it
+            if let Some(previous) = self.rafsi.insert(r.clone(), gismu.clone()) {
+                if let Some(listed) = self.listed.get_mut(&previous) {
+                    listed.retain(|l| *l != r);
+                }
+            }
+            self.listed.entry(gismu.clone()).or_default().push(r);
+        }
+        self.gismu.insert(gismu);
+    }
+
+    /// The rafsi listed for `gismu`, in the order they were inserted.
+    pub(super) fn rafsi_of(&self, gismu: &str) -> Vec<&str> {
+        self.listed
+            .get(gismu)
+            .map(|listed| listed.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// The gismu a rafsi stands for: a listed rafsi, a listed gismu itself, or the listed
+    /// gismu a four-letter form was cut from.
+    pub fn gismu_for(&self, rafsi: &str) -> Option<&str> {
+        let rafsi = normalize(rafsi);
+        if let Some(gismu) = self.rafsi.get(&rafsi) {
+            return Some(gismu);
+        }
+        if let Some(gismu) = self.gismu.get(&rafsi) {
+            return Some(gismu);
+        }
+        match kind_of(&rafsi) {
+            RafsiKind::Cvcc | RafsiKind::Ccvc => self
+                .gismu
+                .range(rafsi.clone()..)
+                .next()
+                .filter(|gismu| gismu.len() == 5 && gismu.starts_with(&rafsi))
+                .map(String::as_str),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use crate::peg::parsing::ParseError;
+use std::fmt::{Display, Formatter};
+
+/// Error types for word analysis
+#[derive(Clone, Debug)]
+pub enum MorphologyError {
+    /// Error when the text is not a single brivla; holds the morphology parse error
+    NotABrivla(ParseError),
+    /// Error when a lujvo is asked for with fewer than two components
+    TooFewComponents,
+    /// Error when a tanru component is neither a gismu nor listed in the rafsi table, or is not
+    /// spelled with Lojban letters
+    NoRafsi(String),
+}
+
+impl Display for MorphologyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MorphologyError::NotABrivla(error) => write!(f, "Not a brivla: {}", error.error),
+            MorphologyError::TooFewComponents => write!(f, "A lujvo needs at least two components"),
+            MorphologyError::NoRafsi(word) => write!(f, "No rafsi known for '{}'", word),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use super::core::{decompose, decompose_with, kind_of, normalize};
+use super::errors::MorphologyError;
+use super::types::{Lujvo, Rafsi, RafsiKind, RafsiTable};
+
+/// Builds every lujvo for a tanru, best (lowest score) first. Each component is a gismu, or
+/// any word `table` lists rafsi for; a gismu also joins with its four-letter form (before a
+/// `y`) or, last, as a whole. Hyphens go in where the morphology requires them, and each
+/// candidate is parsed back so forms that would fall apart (tosmabru, slinku'i) are dropped.
+/// A component spelled with anything but Lojban letters and `'` has no rafsi.
+///
+/// ```rust
+/// use camxes_rs::lojban::morphology::{jvozba, MorphologyError, RafsiTable};
+///
+/// let error = jvozba(&["ĉaĉĉa", "klama"], &RafsiTable::new()).unwrap_err();
+/// assert!(matches!(error, MorphologyError::NoRafsi(word) if word == "ĉaĉĉa"));
+/// ```
+pub fn jvozba(tanru: &[&str], table: &RafsiTable) -> Result<Vec<Lujvo>, MorphologyError> {
+    if tanru.len() < 2 {
+        return Err(MorphologyError::TooFewComponents);
+    }
+    let last = tanru.len() - 1;
+    let choices = tanru
+        .iter()
+        .enumerate()
+        .map(|(i, word)| {
+            let normalized = normalize(word);
+            if !is_lojban(&normalized) {
+                return Err(MorphologyError::NoRafsi(word.to_string()));
+            }
+            let forms = forms(&normalized, table, i == last);
+            if forms.is_empty() {
+                Err(MorphologyError::NoRafsi(word.to_string()))
+            } else {
+                Ok(forms)
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut lujvo: Vec<Lujvo> = vec![];
+    let mut picked = vec![0; choices.len()];
+    loop {
+        let rafsi: Vec<&(String, RafsiKind)> = picked
+            .iter()
+            .zip(&choices)
+            .map(|(&i, forms)| &forms[i])
+            .collect();
+        if let Some(found) = build(&rafsi, table) {
+            if lujvo.iter().all(|l| l.text != found.text) {
+                lujvo.push(found);
+            }
+        }
+        // Next combination, like counting with one digit per component
+        let Some(i) = (0..picked.len())
+            .rev()
+            .find(|&i| picked[i] + 1 < choices[i].len())
+        else {
+            break;
+        };
+        picked[i] += 1;
+        picked[i + 1..].iter_mut().for_each(|p| *p = 0);
+    }
+    lujvo.sort_by(|a, b| a.score.cmp(&b.score).then_with(|| a.text.cmp(&b.text)));
+    Ok(lujvo)
+}
+
+/// The standard lujvo score (CLL 4.12) of a brivla: lower is better.
+pub fn score(lujvo: &str) -> Result<i32, MorphologyError> {
+    Ok(score_parts(&decompose(lujvo)?))
+}
+
+/// The rafsi a component can take: the listed ones, plus the four-letter and whole forms of
+/// a gismu. Only vowel-final forms can end a lujvo.
+fn forms(word: &str, table: &RafsiTable, last: bool) -> Vec<(String, RafsiKind)> {
+    let mut forms: Vec<(String, RafsiKind)> = table
+        .rafsi_of(table.gismu_for(word).unwrap_or(word))
+        .into_iter()
+        .map(|r| (r.to_string(), kind_of(r)))
+        .collect();
+    // kind_of skips `'`, which a gismu never has
+    if kind_of(word) == RafsiKind::Gismu && !word.contains('\'') {
+        if last {
+            forms.push((word.to_string(), RafsiKind::Gismu));
+        } else {
+            let short: String = word.chars().take(4).collect();
+            forms.push((short.clone(), kind_of(&short)));
+        }
+    }
+    forms.retain(|(_, kind)| match kind {
+        RafsiKind::Ccv | RafsiKind::Cvv => true,
+        RafsiKind::Cvc | RafsiKind::Cvcc | RafsiKind::Ccvc => !last,
+        RafsiKind::Gismu => last,
+        RafsiKind::Fuhivla | RafsiKind::Hyphen => false,
+    });
+    forms
+}
+
+/// Joins the rafsi with the hyphens they need, then keeps the result only if it parses back
+/// into the same parts. A leading CVC rafsi that breaks off as a cmavo gets a `y`.
+fn build(rafsi: &[&(String, RafsiKind)], table: &RafsiTable) -> Option<Lujvo> {
+    let mut pieces: Vec<&str> = vec![];
+    for (i, (text, kind)) in rafsi.iter().enumerate() {
+        pieces.push(text);
+        if let Some(next) = rafsi.get(i + 1) {
+            pieces.extend(hyphen(i, (text, *kind), &next.0, rafsi.len()));
+        }
+    }
+
+    let mut lujvo = check(&pieces, table);
+    if lujvo.is_none() && rafsi[0].1 == RafsiKind::Cvc && pieces[1] != "y" {
+        pieces.insert(1, "y");
+        lujvo = check(&pieces, table);
+    }
+    lujvo
+}
+
+/// The hyphen between rafsi `i` and the next one, if one is needed.
+fn hyphen(
+    i: usize,
+    (text, kind): (&str, RafsiKind),
+    next: &str,
+    count: usize,
+) -> Option<&'static str> {
+    match kind {
+        RafsiKind::Cvcc | RafsiKind::Ccvc => Some("y"),
+        RafsiKind::Cvc => {
+            let end = text.chars().last()?;
+            let start = next.chars().next()?;
+            let triple = end == 'n' && ["dj", "dz", "tc", "ts"].iter().any(|c| next.starts_with(c));
+            (!permissible(end, start) || triple).then_some("y")
+        }
+        // A first CVV would break off as a cmavo, unless it only precedes a CCV
+        RafsiKind::Cvv if i == 0 && !(count == 2 && kind_of(next) == RafsiKind::Ccv) => {
+            Some(if next.starts_with('r') { "n" } else { "r" })
+        }
+        _ => None,
+    }
+}
+
+/// Whether `word` is spelled only with Lojban letters and `'`.
+fn is_lojban(word: &str) -> bool {
+    word.chars().all(|c| "abcdefgijklmnoprstuvxyz'".contains(c))
+}
+
+/// Whether two consonants may stand next to each other (CLL 3.6).
+fn permissible(a: char, b: char) -> bool {
+    let voiced = |c| "bdgjvz".contains(c);
+    let unvoiced = |c| "cfkpstx".contains(c);
+    let sibilant = |c| "cjsz".contains(c);
+    !(a == b
+        || (voiced(a) && unvoiced(b))
+        || (unvoiced(a) && voiced(b))
+        || (sibilant(a) && sibilant(b))
+        || matches!(
+            (a, b),
+            ('c', 'x') | ('k', 'x') | ('x', 'c') | ('x', 'k') | ('m', 'z')
+        ))
+}
+
+fn check(pieces: &[&str], table: &RafsiTable) -> Option<Lujvo> {
+    let text = pieces.concat();
+    let parts = decompose_with(&text, table).ok()?;
+    if parts.len() != pieces.len() || parts.iter().zip(pieces).any(|(p, &t)| p.text != t) {
+        return None;
+    }
+    Some(Lujvo {
+        score: score_parts(&parts),
+        text,
+        parts,
+    })
+}
+
+/// L*1000 - 500*A + 100*H - 10*R - V: length, apostrophes, hyphens, rafsi forms and vowels.
+fn score_parts(parts: &[Rafsi]) -> i32 {
+    let mut score = 0;
+    for part in parts {
+        let text = normalize(&part.text);
+        let vowels = text.chars().filter(|c| "aeiou".contains(*c)).count() as i32;
+        let apostrophes = text.matches('\'').count() as i32;
+        let form = match part.kind {
+            RafsiKind::Gismu if text.chars().nth(1).is_some_and(|c| "aeiou".contains(c)) => 1,
+            RafsiKind::Gismu => 3,
+            RafsiKind::Cvcc => 2,
+            RafsiKind::Ccvc => 4,
+            RafsiKind::Cvc => 5,
+            RafsiKind::Cvv if apostrophes > 0 => 6,
+            RafsiKind::Ccv => 7,
+            RafsiKind::Cvv => 8,
+            RafsiKind::Fuhivla | RafsiKind::Hyphen => 0,
+        };
+        let hyphen = (part.kind == RafsiKind::Hyphen) as i32;
+        score += 1000 * text.chars().count() as i32 - 500 * apostrophes + 100 * hyphen
+            - 10 * form
+            - vowels;
+    }
+    score
+}
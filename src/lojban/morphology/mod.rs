@@ -0,0 +1,55 @@
+//! Word-level analysis: splitting a text into classified words and a brivla into its rafsi,
+//! and building lujvo from tanru, using the morphology rules of [`LOJBAN_GRAMMAR`](crate::grammars::LOJBAN_GRAMMAR).
+//!
+//! ```rust
+//! use camxes_rs::lojban::morphology::{
+//!     classify, decompose, decompose_with, jvozba, RafsiKind, RafsiTable, WordClass,
+//! };
+//!
+//! let parts = decompose("klamydja").unwrap();
+//! let texts: Vec<_> = parts.iter().map(|r| r.text.as_str()).collect();
+//! assert_eq!(texts, ["klam", "y", "dja"]);
+//! assert_eq!(parts[0].kind, RafsiKind::Ccvc);
+//! assert_eq!(parts[1].kind, RafsiKind::Hyphen);
+//!
+//! let table = RafsiTable::new()
+//!     .with("klama", &["kla"])
+//!     .with("gasnu", &["gau", "ga'u"]);
+//! let parts = decompose_with("klagau", &table).unwrap();
+//! let gismu: Vec<_> = parts.iter().map(|r| r.gismu.as_deref()).collect();
+//! assert_eq!(gismu, [Some("klama"), Some("gasnu")]);
+//!
+//! assert!(decompose("klamaa").is_err());
+//!
+//! let words = classify("lonu do klamaa la .djan.").unwrap();
+//! let classes: Vec<_> = words.iter().map(|w| (w.text.as_str(), w.class)).collect();
+//! assert_eq!(
+//!     classes,
+//!     [
+//!         ("lo", WordClass::Cmavo),
+//!         ("nu", WordClass::Cmavo),
+//!         ("do", WordClass::Cmavo),
+//!         ("klamaa", WordClass::NonLojban),
+//!         ("la", WordClass::Cmavo),
+//!         ("djan", WordClass::Cmevla),
+//!     ]
+//! );
+//! assert_eq!(words[0].selmaho.as_deref(), Some("LE"));
+//!
+//! let table = table.with("zdani", &["dan", "zda"]).with("karce", &["kar", "ka'e"]);
+//! let best = &jvozba(&["zdani", "karce"], &table).unwrap()[0];
+//! assert_eq!(best.text, "zdaka'e");
+//! assert_eq!(best.score, 6367);
+//! ```
+
+mod core;
+mod errors;
+mod jvozba;
+mod types;
+mod words;
+
+pub use self::core::{decompose, decompose_with};
+pub use self::errors::MorphologyError;
+pub use self::jvozba::{jvozba, score};
+pub use self::types::{CmavoTable, Lujvo, Rafsi, RafsiKind, RafsiTable, Word, WordClass};
+pub use self::words::{classify, classify_with};
@@ -0,0 +1,86 @@
+use crate::peg::parsing::Span;
+use std::collections::{BTreeSet, HashMap};
+
+/// The form of one part of a brivla, by its letters (`'` is not counted).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RafsiKind {
+    /// Consonant-vowel-consonant, e.g. `zos`
+    Cvc,
+    /// Consonant-consonant-vowel, e.g. `kla`
+    Ccv,
+    /// Consonant and two vowels, e.g. `gau` or `ba'i`
+    Cvv,
+    /// A gismu without its final vowel, e.g. `mint` in `mintykla`
+    Cvcc,
+    /// A gismu without its final vowel, e.g. `klam` in `klamydja`
+    Ccvc,
+    /// A whole gismu, as the last part or before `'y`
+    Gismu,
+    /// A fu'ivla, or a fu'ivla used as a rafsi, e.g. `arb` in `arbymla`
+    Fuhivla,
+    /// A joining hyphen: `y`, `'y`, `r` or `n`
+    Hyphen,
+}
+
+/// One part of a brivla: a rafsi or a hyphen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rafsi {
+    /// The part as written
+    pub text: String,
+    pub kind: RafsiKind,
+    /// Byte range in the analysed text
+    pub span: Span,
+    /// The gismu the rafsi stands for, when a [`RafsiTable`] knows it
+    pub gismu: Option<String>,
+}
+
+/// Maps rafsi to their gismu, e.g. from the official rafsi list. The four-letter forms of a
+/// gismu (`klam` for `klama`) are found without being listed.
+#[derive(Clone, Debug, Default)]
+pub struct RafsiTable {
+    pub(super) rafsi: HashMap<String, String>,
+    pub(super) gismu: BTreeSet<String>,
+    /// The rafsi of each gismu, in the order they were listed
+    pub(super) listed: HashMap<String, Vec<String>>,
+}
+
+/// A lujvo built by [`jvozba`](super::jvozba), with its parts and score.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lujvo {
+    pub text: String,
+    /// The rafsi and hyphens, as [`decompose_with`](super::decompose_with) finds them
+    pub parts: Vec<Rafsi>,
+    /// The lujvo score; lower is better
+    pub score: i32,
+}
+
+/// The morphological class of a word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WordClass {
+    Gismu,
+    Lujvo,
+    Fuhivla,
+    Cmavo,
+    Cmevla,
+    /// Text between pauses that is not a Lojban word, e.g. a misspelling
+    NonLojban,
+}
+
+/// One word of a text, as split by the morphology.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Word {
+    /// The word as written
+    pub text: String,
+    pub class: WordClass,
+    /// Byte range in the classified text
+    pub span: Span,
+    /// The selma'o of a cmavo: from a [`CmavoTable`] if it lists the word, otherwise from the
+    /// grammar, which knows the official cmavo
+    pub selmaho: Option<String>,
+}
+
+/// Maps cmavo to their selma'o, e.g. to cover experimental cmavo the grammar does not know.
+#[derive(Clone, Debug, Default)]
+pub struct CmavoTable {
+    pub(super) selmaho: HashMap<String, String>,
+}
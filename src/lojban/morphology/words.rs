@@ -0,0 +1,79 @@
+use super::core::{normalize, LOJBAN};
+use super::types::{CmavoTable, Word, WordClass};
+use crate::peg::parsing::{ParseError, ParseNode};
+
+/// Splits `text` into words and classifies each. Spaces, commas and the pause marks `.`, `?`
+/// and `!` are skipped; any other stretch of text that is not a Lojban word comes back as
+/// [`WordClass::NonLojban`]. Empty text gives no words; an error means the text could not be
+/// split at all.
+pub fn classify(text: &str) -> Result<Vec<Word>, ParseError> {
+    let result = LOJBAN.parse(text);
+    let nodes = result.as_result().map_err(Clone::clone)?;
+    Ok(nodes
+        .iter()
+        .flat_map(ParseNode::children)
+        .filter_map(|node| word(node, text))
+        .collect())
+}
+
+/// [`classify`], taking the selma'o of cmavo that `table` lists from the table.
+pub fn classify_with(text: &str, table: &CmavoTable) -> Result<Vec<Word>, ParseError> {
+    let mut words = classify(text)?;
+    for word in words.iter_mut().filter(|w| w.class == WordClass::Cmavo) {
+        if let Some(selmaho) = table.selmaho_for(&word.text) {
+            word.selmaho = Some(selmaho.to_string());
+        }
+    }
+    Ok(words)
+}
+
+/// The word a child of the `words` rule stands for; `None` for pauses.
+fn word(node: &ParseNode, input: &str) -> Option<Word> {
+    let (class, selmaho) = match node.name()? {
+        "lojban_word" => {
+            // lojban_word <- CMEVLA / CMAVO / BRIVLA; the rule below names the class
+            let kind = node.children().first()?;
+            let form = kind.children().first()?.name()?;
+            match (kind.name()?, form) {
+                ("CMEVLA", _) => (WordClass::Cmevla, None),
+                ("CMAVO", "cmavo") => (WordClass::Cmavo, None),
+                ("CMAVO", selmaho) => (WordClass::Cmavo, Some(selmaho.to_string())),
+                ("BRIVLA", "gismu") => (WordClass::Gismu, None),
+                ("BRIVLA", "fuhivla") => (WordClass::Fuhivla, None),
+                ("BRIVLA", _) => (WordClass::Lujvo, None),
+                _ => return None,
+            }
+        }
+        "non_lojban_word" => (WordClass::NonLojban, None),
+        _ => return None,
+    };
+    Some(Word {
+        text: node.text(input).to_string(),
+        class,
+        span: node.span(),
+        selmaho,
+    })
+}
+
+impl CmavoTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds cmavo of a selma'o; see [`insert`](Self::insert).
+    pub fn with(mut self, selmaho: &str, cmavo: &[&str]) -> Self {
+        self.insert(selmaho, cmavo);
+        self
+    }
+
+    /// Adds cmavo of a selma'o, replacing the selma'o they were listed under before.
+    pub fn insert(&mut self, selmaho: &str, cmavo: &[&str]) {
+        for c in cmavo {
+            self.selmaho.insert(normalize(c), selmaho.to_string());
+        }
+    }
+
+    pub fn selmaho_for(&self, cmavo: &str) -> Option<&str> {
+        self.selmaho.get(&normalize(cmavo)).map(String::as_str)
+    }
+}
@@ -0,0 +1,363 @@
+//! Human-readable error reports: the offending source line with a caret under the failure,
+//! the rule stack and the expected tokens, optionally colored with ANSI escapes.
+//!
+//! ```rust
+//! use camxes_rs::peg::diagnostic::Diagnostic;
+//! use camxes_rs::peg::grammar::Peg;
+//!
+//! let peg = Peg::new("number", "number <- [0-9]+ !.").unwrap();
+//! let input = "12a";
+//! if let Some(error) = peg.parse(input).error() {
+//!     eprintln!("{}", Diagnostic::new(input).render_parse_error(error));
+//! }
+//! ```
+
+use crate::peg::grammar::GrammarError;
+use crate::peg::parsing::{line_column, ErrorKind, ParseError, ParseNode, Span};
+use crate::peg::transformer::{LintKind, LintWarning, TransformError};
+use std::fmt::Write;
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders errors against the text they were produced from.
+#[derive(Clone, Copy, Debug)]
+pub struct Diagnostic<'a> {
+    source: &'a str,
+    color: bool,
+}
+
+impl<'a> Diagnostic<'a> {
+    /// `source` must be the exact text that was parsed (the input, or the grammar text for
+    /// errors coming out of `Peg::new`).
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            color: false,
+        }
+    }
+
+    /// Enables or disables ANSI colors (off by default).
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn render_parse_error(&self, error: &ParseError) -> String {
+        let mut out = String::new();
+        match &error.error {
+            ErrorKind::Expected {
+                expected,
+                found,
+                rule_stack,
+                ..
+            } => {
+                let found_text = match found {
+                    Some(c) => format!("found {:?}", c),
+                    None => "found end of input".to_string(),
+                };
+                self.header(&mut out, &format!("unexpected input, {}", found_text));
+                let end = error.position + found.map_or(0, char::len_utf8);
+                self.snippet(&mut out, error.position, end, &expected_label(expected));
+                if !rule_stack.is_empty() {
+                    self.note(&mut out, "while parsing", &rule_stack.join(" > "));
+                }
+                self.note(&mut out, "expected", &expected.join(", "));
+            }
+            ErrorKind::UnconsumedInput { at } => {
+                self.header(
+                    &mut out,
+                    &format!("'{}' does not match the whole input", error.rule_name),
+                );
+                let line_end = self.source[*at..]
+                    .find('\n')
+                    .map_or(self.source.len(), |i| at + i);
+                self.snippet(&mut out, *at, line_end, "not matched");
+                if let Some(ParseError {
+                    position,
+                    error:
+                        ErrorKind::Expected {
+                            expected,
+                            rule_stack,
+                            ..
+                        },
+                    ..
+                }) = error.cause.as_deref()
+                {
+                    if position != at {
+                        let (line, column) = line_column(self.source, *position);
+                        self.note(
+                            &mut out,
+                            "farthest failure",
+                            &format!("{}:{}", line, column),
+                        );
+                    }
+                    if !rule_stack.is_empty() {
+                        self.note(&mut out, "while parsing", &rule_stack.join(" > "));
+                    }
+                    self.note(&mut out, "expected", &expected.join(", "));
+                }
+            }
+            kind => {
+                self.header(&mut out, &format!("{} in '{}'", kind, error.rule_name));
+                let end = match kind {
+                    ErrorKind::NotDidMatch(nodes) => nodes_end(nodes).unwrap_or(error.position),
+                    ErrorKind::ActionFailed { end, .. } => *end,
+                    _ => error.position,
+                };
+                self.snippet(&mut out, error.position, end, "");
+                let chain = cause_chain(error);
+                if chain.len() > 1 {
+                    self.note(&mut out, "while parsing", &chain.join(" > "));
+                }
+            }
+        }
+        out
+    }
+
+    /// Renders an error returned by `Peg::new`; `self` must wrap the grammar text.
+    ///
+    /// ```rust
+    /// use camxes_rs::peg::diagnostic::Diagnostic;
+    /// use camxes_rs::peg::grammar::Peg;
+    ///
+    /// let grammar = "greeting <- 'hi' name\n";
+    /// let error = Peg::new("greeting", grammar).unwrap_err();
+    /// let report = Diagnostic::new(grammar).render_grammar_error(&error);
+    /// assert!(report.starts_with("error: rule 'name' is not defined"));
+    /// assert!(report.contains("1 | greeting <- 'hi' name\n  |                  ^^^^ used here"));
+    /// ```
+    pub fn render_grammar_error(&self, error: &GrammarError) -> String {
+        match error {
+            GrammarError::Parse(err) => {
+                let mut out = self.render_parse_error(err);
+                self.note(
+                    &mut out,
+                    "note",
+                    "the grammar definition could not be parsed",
+                );
+                out
+            }
+            GrammarError::Transform(TransformError::DuplicateRule {
+                name,
+                first,
+                second,
+            }) => {
+                let mut out = String::new();
+                self.header(&mut out, &format!("rule '{}' is defined twice", name));
+                self.snippet(&mut out, second.0, second.1, "redefined here");
+                let (line, column) = line_column(self.source, first.0);
+                self.note(
+                    &mut out,
+                    "first defined at",
+                    &format!("{}:{}", line, column),
+                );
+                self.note(
+                    &mut out,
+                    "help",
+                    "rename one of them, or build with `GrammarOptions::with_overrides` to keep the later one",
+                );
+                out
+            }
+            GrammarError::Transform(TransformError::AmbiguousNonTerminal { references }) => {
+                let mut names: Vec<_> = references.iter().map(|(name, _)| name.as_str()).collect();
+                names.sort_unstable();
+                names.dedup();
+                let mut out = String::new();
+                match names.as_slice() {
+                    [name] => self.header(&mut out, &format!("rule '{}' is not defined", name)),
+                    _ => self.header(
+                        &mut out,
+                        &format!("rules {} are not defined", quoted(&names)),
+                    ),
+                }
+                for (_, Span(start, end)) in references {
+                    self.snippet(&mut out, *start, *end, "used here");
+                }
+                out
+            }
+            GrammarError::Transform(TransformError::UnsupportedLeftRecursion { rules }) => {
+                let names: Vec<_> = rules.iter().map(|(name, _)| name.as_str()).collect();
+                let mut out = String::new();
+                self.header(
+                    &mut out,
+                    &format!(
+                        "rules {} are left-recursive with no rule on every cycle",
+                        quoted(&names)
+                    ),
+                );
+                for (_, span) in rules {
+                    if let Some(Span(start, end)) = span {
+                        self.snippet(&mut out, *start, *end, "part of the cycle");
+                    }
+                }
+                self.note(
+                    &mut out,
+                    "help",
+                    "rewrite the rules so that one of them lies on every path of the cycle",
+                );
+                out
+            }
+            GrammarError::Transform(TransformError::InvalidEscape { escape, span }) => {
+                let mut out = String::new();
+                self.header(&mut out, &format!("invalid escape sequence '{}'", escape));
+                self.snippet(&mut out, span.0, span.1, "not a valid code point");
+                out
+            }
+            // The rest describe a malformed tree from the grammar parser, not the grammar text
+            GrammarError::Transform(err) => {
+                let mut out = String::new();
+                self.header(&mut out, &err.to_string());
+                out
+            }
+        }
+    }
+
+    /// Renders a warning returned by `Peg::lint`; `self` must wrap the grammar text.
+    pub fn render_lint(&self, warning: &LintWarning) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{}: {}",
+            self.paint(YELLOW, "warning"),
+            self.paint(BOLD, &warning.to_string())
+        );
+        let Span(start, end) = warning.span;
+        self.snippet(&mut out, start, end, "");
+        match warning.kind {
+            LintKind::ShadowedAlternative { by } => {
+                let (line, column) = line_column(self.source, by.0);
+                let message = format!("{} at {}:{}", &self.source[by.0..by.1], line, column);
+                self.note(&mut out, "shadowed by", &message);
+            }
+            LintKind::DuplicateDefinition { replaced_by } => {
+                let (line, column) = line_column(self.source, replaced_by.0);
+                self.note(&mut out, "redefined at", &format!("{}:{}", line, column));
+            }
+            _ => {}
+        }
+        out
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn header(&self, out: &mut String, message: &str) {
+        let _ = writeln!(
+            out,
+            "{}: {}",
+            self.paint(RED, "error"),
+            self.paint(BOLD, message)
+        );
+    }
+
+    /// Prints the line containing `start` and underlines `start..end` (at least one column).
+    fn snippet(&self, out: &mut String, start: usize, end: usize, label: &str) {
+        let start = floor_char_boundary(self.source, start);
+        let (line, column) = line_column(self.source, start);
+        let line_start = self.source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.source[line_start..]
+            .find('\n')
+            .map_or(self.source.len(), |i| line_start + i);
+        let text = self.source[line_start..line_end].trim_end_matches('\r');
+
+        let number = line.to_string();
+        let gutter = " ".repeat(number.len());
+        let bar = self.paint(BLUE, "|");
+
+        let _ = writeln!(
+            out,
+            "{}{} {}:{}",
+            gutter,
+            self.paint(BLUE, "-->"),
+            line,
+            column
+        );
+        let _ = writeln!(out, "{} {}", gutter, bar);
+        let _ = writeln!(out, "{} {} {}", self.paint(BLUE, &number), bar, text);
+
+        // Keep tabs so the caret lines up with the text above it.
+        let padding: String = text
+            .chars()
+            .take(column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let end = end.clamp(start, line_end.max(start));
+        let width = self.source[start..end].chars().count().max(1);
+        let marker = self.paint(RED, &"^".repeat(width));
+        if label.is_empty() {
+            let _ = writeln!(out, "{} {} {}{}", gutter, bar, padding, marker);
+        } else {
+            let _ = writeln!(
+                out,
+                "{} {} {}{} {}",
+                gutter,
+                bar,
+                padding,
+                marker,
+                self.paint(RED, label)
+            );
+        }
+    }
+
+    fn note(&self, out: &mut String, title: &str, message: &str) {
+        let _ = writeln!(
+            out,
+            "  {} {}: {}",
+            self.paint(BLUE, "="),
+            self.paint(BOLD, title),
+            message
+        );
+    }
+}
+
+fn quoted(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|name| format!("'{}'", name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn expected_label(expected: &[String]) -> String {
+    match expected {
+        [single] => format!("expected {}", single),
+        _ => format!("expected one of {} alternatives", expected.len()),
+    }
+}
+
+/// Rule names along the `cause` chain, outermost first.
+fn cause_chain(error: &ParseError) -> Vec<String> {
+    let mut chain = vec![error.rule_name.clone()];
+    let mut current = error;
+    while let Some(inner) = &current.cause {
+        chain.push(inner.rule_name.clone());
+        current = inner;
+    }
+    chain
+}
+
+fn nodes_end(nodes: &[ParseNode]) -> Option<usize> {
+    nodes.last().map(|node| match node {
+        ParseNode::Terminal { span: Span(_, e) }
+        | ParseNode::NonTerminal {
+            span: Span(_, e), ..
+        } => *e,
+    })
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
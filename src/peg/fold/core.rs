@@ -0,0 +1,106 @@
+use super::types::{Actions, Fold, FoldContext};
+use crate::peg::parsing::{ErrorKind, ParseError, ParseNode, Span};
+
+impl ParseNode {
+    /// Folds this node and everything below it into the values it passes to its parent.
+    pub fn fold<F: Fold + ?Sized>(
+        &self,
+        input: &str,
+        fold: &mut F,
+    ) -> Result<Vec<F::Output>, ParseError> {
+        let ctx = FoldContext { node: self, input };
+        let values = match self {
+            ParseNode::Terminal { .. } => fold.terminal(&ctx).map(Vec::from_iter),
+            ParseNode::NonTerminal { children, .. } => {
+                let mut values = Vec::with_capacity(children.len());
+                for child in children {
+                    values.extend(child.fold(input, fold)?);
+                }
+                fold.rule(&ctx, values)
+            }
+        };
+        values.map_err(|message| ctx.error(message))
+    }
+}
+
+impl FoldContext<'_> {
+    pub fn node(&self) -> &ParseNode {
+        self.node
+    }
+
+    /// The rule that produced the node, `None` for terminals.
+    pub fn name(&self) -> Option<&str> {
+        self.node.name()
+    }
+
+    pub fn span(&self) -> Span {
+        self.node.span()
+    }
+
+    /// The text the node covers.
+    pub fn text(&self) -> &str {
+        self.node.text(self.input)
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        let Span(start, end) = self.span();
+        ParseError {
+            position: start,
+            rule_name: self.name().unwrap_or("terminal").to_string(),
+            error: ErrorKind::ActionFailed { message, end },
+            cause: None,
+        }
+    }
+}
+
+impl<'a, T> Actions<'a, T> {
+    pub fn new() -> Self {
+        Self {
+            handlers: Default::default(),
+            terminal: None,
+        }
+    }
+
+    /// Computes the value of each `rule` node from the values of its children.
+    pub fn on(
+        mut self,
+        rule: &str,
+        handler: impl FnMut(&FoldContext<'_>, Vec<T>) -> Result<T, String> + 'a,
+    ) -> Self {
+        self.handlers.insert(rule.to_string(), Box::new(handler));
+        self
+    }
+
+    /// Gives every terminal a value.
+    pub fn on_terminal(
+        mut self,
+        handler: impl FnMut(&FoldContext<'_>) -> Result<T, String> + 'a,
+    ) -> Self {
+        self.terminal = Some(Box::new(handler));
+        self
+    }
+}
+
+impl<T> Default for Actions<'_, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Fold for Actions<'_, T> {
+    type Output = T;
+
+    fn rule(&mut self, ctx: &FoldContext<'_>, children: Vec<T>) -> Result<Vec<T>, String> {
+        match ctx.name().and_then(|name| self.handlers.get_mut(name)) {
+            Some(handler) => Ok(vec![handler(ctx, children)?]),
+            None => Ok(children),
+        }
+    }
+
+    fn terminal(&mut self, ctx: &FoldContext<'_>) -> Result<Option<T>, String> {
+        self.terminal
+            .as_mut()
+            .map(|handler| handler(ctx))
+            .transpose()
+    }
+}
@@ -0,0 +1,21 @@
+//! Turning parse trees into your own types: a [`Fold`] computes values for nodes bottom up,
+//! and [`Actions`] implements it with a handler registered per rule name.
+//!
+//! ```rust
+//! use camxes_rs::peg::fold::Actions;
+//! use camxes_rs::peg::grammar::Peg;
+//!
+//! let peg = Peg::new("sum", "sum <- num ('+' num)*\nnum <- [0-9]+").unwrap();
+//! let mut actions = Actions::new()
+//!     .on("num", |ctx, _| ctx.text().parse::<i64>().map_err(|e| e.to_string()))
+//!     .on("sum", |_, values| Ok(values.into_iter().sum()));
+//!
+//! assert_eq!(peg.parse_with("1+20+3", &mut actions).unwrap(), 24);
+//! let error = peg.parse_with("1+99999999999999999999", &mut actions).unwrap_err();
+//! assert_eq!(error.position, 2);
+//! ```
+
+mod core;
+mod types;
+
+pub use self::types::{Actions, Fold, FoldContext};
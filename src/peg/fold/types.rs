@@ -0,0 +1,41 @@
+use crate::peg::parsing::ParseNode;
+use std::collections::HashMap;
+
+/// Computes values for parse nodes from the values of the nodes below them, bottom up.
+///
+/// The default methods make every node transparent: a rule node passes its children's values
+/// on to its parent and terminals produce none. An `Err` stops the fold and surfaces as
+/// `ErrorKind::ActionFailed` at the node's position.
+pub trait Fold {
+    type Output;
+
+    /// Values a node produced by rule `ctx.name()` passes to its parent, given the values of
+    /// its children (usually a single value, or `children` unchanged).
+    fn rule(
+        &mut self,
+        _ctx: &FoldContext<'_>,
+        children: Vec<Self::Output>,
+    ) -> Result<Vec<Self::Output>, String> {
+        Ok(children)
+    }
+
+    fn terminal(&mut self, _ctx: &FoldContext<'_>) -> Result<Option<Self::Output>, String> {
+        Ok(None)
+    }
+}
+
+/// The node being folded, with the text that was parsed.
+#[derive(Clone, Copy, Debug)]
+pub struct FoldContext<'a> {
+    pub(super) node: &'a ParseNode,
+    pub(super) input: &'a str,
+}
+
+type Handler<'a, T> = Box<dyn FnMut(&FoldContext<'_>, Vec<T>) -> Result<T, String> + 'a>;
+type TerminalHandler<'a, T> = Box<dyn FnMut(&FoldContext<'_>) -> Result<T, String> + 'a>;
+
+/// A [`Fold`] built from closures, one per rule name. Rules without a handler are transparent.
+pub struct Actions<'a, T> {
+    pub(super) handlers: HashMap<String, Handler<'a, T>>,
+    pub(super) terminal: Option<TerminalHandler<'a, T>>,
+}
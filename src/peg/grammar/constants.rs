@@ -2,6 +2,8 @@
 pub const TEXT: &str = "text";
 pub const EOF: &str = "EndOfFile";
 pub const SPACING: &str = "Spacing";
+pub const COMMENT: &str = "Comment";
+pub const EOL: &str = "EndOfLine";
 
 // Operators and delimiters
 pub const ARROW: &str = "LEFTARROW";
@@ -20,7 +22,9 @@ pub const CHAR: &str = "Char";
 pub const RANGE: &str = "Range";
 pub const CLASS_MEMBER: &str = "ClassMember";
 pub const CLASS: &str = "Class";
+pub const NEGATE: &str = "Negate";
 pub const LITERAL: &str = "Literal";
+pub const IGNORE_CASE: &str = "IgnoreCase";
 
 // Grammar structure elements
 pub const IDENT: &str = "Identifier";
@@ -30,3 +34,4 @@ pub const SUFFIX: &str = "Suffix";
 pub const PREFIX: &str = "Prefix";
 pub const SEQUENCE: &str = "Sequence";
 pub const DEF: &str = "Definition";
+pub const ANNOTATION: &str = "Annotation";
@@ -1,35 +1,143 @@
 use super::constants::*;
 use super::errors::GrammarError;
-use super::types::{MemoMap, Peg};
-use crate::peg::parsing::ParseResult;
-use crate::peg::rule::Rule;
-use crate::peg::transformer::Transformer;
+use super::types::{
+    Expected, FarthestFailure, GrammarOptions, MatchMode, MemoTable, NodeShape, ParseContext, Peg,
+    PegBuilder, Shaping, TreeShape,
+};
+use crate::peg::fold::Fold;
+use crate::peg::parsing::{
+    line_column, ErrorKind, ParseError, ParseNode, ParseResult, Parsed, Span,
+};
+use crate::peg::rule::{Annotation, Rule, RuleId, RuleTable};
+use crate::peg::transformer::{Fragment, LintWarning, Transformer};
 use serde_json; // Import serde_json
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
 type GrammarResult<T> = Result<T, GrammarError>;
 
+/// What a full-input parse expects after the rule's match.
+const END_OF_INPUT: &str = "end of input";
+
 impl Peg {
     pub fn new(start: &str, grammar: &str) -> GrammarResult<Self> {
-        let parser = Self::bootstrap();
-        match parser.parse(grammar) {
-            ParseResult(_, _, ref payload) => match payload.as_ref() {
-                Ok(tokens) => Transformer {
-                    source: grammar.trim(),
-                }
-                .build(start, tokens.clone())
-                .map_err(GrammarError::from),
-                Err(e) => Err(GrammarError::from(e.clone())),
-            },
+        Self::with_options(start, grammar, GrammarOptions::default())
+    }
+
+    /// Like [`Peg::new`], with non-default build settings.
+    ///
+    /// ```rust
+    /// use camxes_rs::peg::grammar::{GrammarOptions, Peg};
+    ///
+    /// let base = "greeting <- 'hello'\n";
+    /// let local = "greeting <- 'hi'\n";
+    /// let grammar = format!("{base}{local}");
+    ///
+    /// assert!(Peg::new("greeting", &grammar).is_err());
+    /// let options = GrammarOptions::default().with_overrides(true);
+    /// let peg = Peg::with_options("greeting", &grammar, options).unwrap();
+    /// assert!(peg.parse("hi").is_ok());
+    /// ```
+    pub fn with_options(
+        start: &str,
+        grammar: &str,
+        options: GrammarOptions,
+    ) -> GrammarResult<Self> {
+        let tokens = Self::parse_grammar(grammar)?;
+        Transformer {
+            source: grammar,
+            options,
+        }
+        .build(start, tokens)
+        .map_err(GrammarError::from)
+    }
+
+    /// Checks a grammar for likely mistakes without building it: an undefined start rule,
+    /// unused and unreachable rules, `*`/`+` over expressions that can match empty input,
+    /// alternatives shadowed by earlier ones, reversed class ranges and duplicate definitions. Spans in the warnings index into `grammar`.
+    ///
+    /// ```rust
+    /// use camxes_rs::peg::grammar::Peg;
+    /// use camxes_rs::peg::transformer::LintKind;
+    ///
+    /// let warnings = Peg::lint("word", "word <- 'a' / 'ab'").unwrap();
+    /// assert!(matches!(warnings[0].kind, LintKind::ShadowedAlternative { .. }));
+    ///
+    /// // A definition replaced by a later one is still checked as written
+    /// let warnings = Peg::lint("word", "word <- ('a'?)*\nword <- 'b'").unwrap();
+    /// let kinds: Vec<_> = warnings.iter().map(|w| &w.kind).collect();
+    /// assert!(matches!(
+    ///     kinds[..],
+    ///     [LintKind::DuplicateDefinition { .. }, LintKind::NullableRepetition]
+    /// ));
+    ///
+    /// let warnings = Peg::lint("word", "word <- [z-a]").unwrap();
+    /// assert_eq!(warnings[0].kind, LintKind::ReversedRange);
+    /// assert_eq!(warnings[0].span.0, 9);
+    ///
+    /// let warnings = Peg::lint("start", "word <- 'a'").unwrap();
+    /// assert_eq!(warnings[0].kind, LintKind::UndefinedStartRule);
+    /// assert_eq!(warnings[0].rule, "start");
+    /// ```
+    pub fn lint(start: &str, grammar: &str) -> GrammarResult<Vec<LintWarning>> {
+        let tokens = Self::parse_grammar(grammar)?;
+        Transformer {
+            source: grammar,
+            options: GrammarOptions::default(),
         }
+        .lint(start, &tokens)
+        .map_err(GrammarError::from)
     }
 
+    /// Parses `input` from the start rule. The whole input has to match: trailing text fails
+    /// with `ErrorKind::UnconsumedInput` (see [`MatchMode`] to allow prefix matches).
     pub fn parse(&self, input: &str) -> ParseResult {
-        // Clear the memoization cache before starting a new parse
-        self.memo.borrow_mut().clear();
-        Rule::NonTerminal(self.start.clone()).parse(self, input, 0, 0)
+        self.context().parse(input)
+    }
+
+    /// Like [`Peg::parse`], returning the parse tree or the error as a `Result`.
+    pub fn try_parse(&self, input: &str) -> Result<Vec<ParseNode>, ParseError> {
+        self.parse(input).into_result()
+    }
+
+    /// Parses `input` and folds the tree into a single value (see [`Fold`]). Fails when
+    /// parsing fails, when the fold rejects a node, or when the tree does not fold into
+    /// exactly one value (e.g. the start rule has no handler and several children do).
+    pub fn parse_with<F: Fold + ?Sized>(
+        &self,
+        input: &str,
+        fold: &mut F,
+    ) -> Result<F::Output, ParseError> {
+        let mut values = vec![];
+        for node in self.try_parse(input)? {
+            values.extend(node.fold(input, fold)?);
+        }
+        let count = values.len();
+        match (values.pop(), count) {
+            (Some(value), 1) => Ok(value),
+            _ => Err(ParseError {
+                position: 0,
+                rule_name: self.start.clone(),
+                error: ErrorKind::ActionFailed {
+                    message: format!("expected the tree to fold into one value, got {}", count),
+                    end: input.len(),
+                },
+                cause: None,
+            }),
+        }
+    }
+
+    /// Parses `input` starting from `rule` rather than the start rule, using the same compiled
+    /// rules. To accept a match of just a prefix use a context:
+    /// `peg.context().with_mode(MatchMode::Prefix).parse_rule(rule, input)`.
+    pub fn parse_rule(&self, rule: &str, input: &str) -> ParseResult {
+        self.context().parse_rule(rule, input)
+    }
+
+    /// Creates a parse session for this grammar. Use it directly to reuse the memo
+    /// table's allocation across several inputs on the same thread.
+    pub fn context(&self) -> ParseContext<'_> {
+        ParseContext::new(self)
     }
 
     /// Parses the input and returns the result as a JSON string.
@@ -39,6 +147,74 @@ impl Peg {
         serde_json::to_string_pretty(&parse_result) // Use pretty print for readability
     }
 
+    /// Shapes the trees of later parses while they are built: merges adjacent terminals,
+    /// hides or discards the nodes of matching rules and collapses single-child chains.
+    /// Patterns that name no rule are ignored.
+    ///
+    /// ```rust
+    /// use camxes_rs::peg::grammar::{Peg, TreeShape};
+    ///
+    /// let grammar = "list <- word (sp word)*\nword <- letter+\nletter <- [a-z]\nsp <- ' '+";
+    /// let shape = TreeShape::default()
+    ///     .merge_terminals(true)
+    ///     .hide("letter")
+    ///     .discard("sp");
+    /// let peg = Peg::new("list", grammar).unwrap().with_shape(shape);
+    ///
+    /// let input = "mi  cluva";
+    /// let result = peg.parse(input);
+    /// let words = result.root().unwrap().children();
+    /// assert_eq!(words.len(), 2);
+    /// assert_eq!(words[1].text(input), "cluva");
+    /// // One terminal for the whole word instead of a `letter` node per character.
+    /// assert_eq!(words[1].children().len(), 1);
+    /// ```
+    pub fn with_shape(mut self, shape: TreeShape) -> Self {
+        let annotated = (0..self.rules.len()).any(|id| self.rules.annotation(id).is_some());
+        let nodes: Vec<NodeShape> =
+            if !annotated && shape.hidden.is_empty() && shape.discarded.is_empty() {
+                vec![]
+            } else {
+                let matches = |patterns: &[String], name: &str| {
+                    patterns
+                        .iter()
+                        .any(|pattern| pattern_matches(pattern, name))
+                };
+                self.rules
+                    .iter()
+                    .enumerate()
+                    .map(|(id, (name, _))| {
+                        if let Some(annotation) = self.rules.annotation(id) {
+                            match annotation {
+                                Annotation::Silent => NodeShape::Discard,
+                                Annotation::Token => NodeShape::Token,
+                                Annotation::Keep => NodeShape::Pinned,
+                            }
+                        } else if matches(&shape.discarded, name) {
+                            NodeShape::Discard
+                        } else if matches(&shape.hidden, name) {
+                            NodeShape::Hide
+                        } else {
+                            NodeShape::Keep
+                        }
+                    })
+                    .collect()
+            };
+        self.shaping = Shaping {
+            merge_terminals: shape.merge_terminals,
+            collapse_chains: shape.collapse_chains,
+            nodes: nodes.into(),
+        };
+        self
+    }
+
+    /// Parses grammar text into its concrete syntax tree.
+    fn parse_grammar(grammar: &str) -> GrammarResult<Vec<ParseNode>> {
+        Self::bootstrap()
+            .parse(grammar)
+            .into_result()
+            .map_err(GrammarError::from)
+    }
 
     fn bootstrap() -> Self {
         let mut grammar_builder = RuleBuilder::new();
@@ -49,8 +225,288 @@ impl Peg {
 
         Self {
             start: TEXT.to_string(),
-            rules: Arc::new(grammar_builder.rules),
-            memo: RefCell::new(MemoMap::default()),
+            rules: Arc::new(
+                RuleTable::compile(grammar_builder.rules)
+                    .expect("the bootstrap grammar is not left-recursive"),
+            ),
+            shaping: Shaping::default(),
+        }
+    }
+}
+
+/// `prefix*`, `*suffix` or an exact rule name.
+fn pattern_matches(pattern: &str, name: &str) -> bool {
+    if let Some(prefix) = pattern.strip_suffix('*') {
+        name.starts_with(prefix)
+    } else if let Some(suffix) = pattern.strip_prefix('*') {
+        name.ends_with(suffix)
+    } else {
+        pattern == name
+    }
+}
+
+impl TreeShape {
+    /// Joins terminals that follow each other directly into a single terminal, so a word
+    /// becomes one token rather than one node per character.
+    pub fn merge_terminals(mut self, merge: bool) -> Self {
+        self.merge_terminals = merge;
+        self
+    }
+
+    /// Replaces a node whose only child is a non-terminal by that child, keeping the innermost
+    /// rule of a chain like `sentence > predicate > predunit`.
+    pub fn collapse_chains(mut self, collapse: bool) -> Self {
+        self.collapse_chains = collapse;
+        self
+    }
+
+    /// Nodes of rules matching `pattern` are replaced by their children, for helper rules whose
+    /// text should stay in the tree (e.g. morphology rules such as `Vo1`).
+    pub fn hide(mut self, pattern: &str) -> Self {
+        self.hidden.push(pattern.to_string());
+        self
+    }
+
+    /// Nodes of rules matching `pattern` are left out with everything below them, e.g.
+    /// whitespace rules such as `sp`. Takes precedence over [`hide`](Self::hide).
+    pub fn discard(mut self, pattern: &str) -> Self {
+        self.discarded.push(pattern.to_string());
+        self
+    }
+}
+
+impl Shaping {
+    pub fn node(&self, id: RuleId) -> NodeShape {
+        self.nodes.get(id).copied().unwrap_or_default()
+    }
+
+    /// Appends `nodes` to `captures`, merging a terminal into a preceding one that ends where
+    /// it starts.
+    pub fn append(&self, captures: &mut Vec<ParseNode>, nodes: &[ParseNode]) {
+        if !self.merge_terminals {
+            captures.extend(nodes.iter().cloned());
+            return;
+        }
+        for node in nodes {
+            if let (Some(ParseNode::Terminal { span: last }), ParseNode::Terminal { span }) =
+                (captures.last_mut(), node)
+            {
+                if last.1 == span.0 {
+                    last.1 = span.1;
+                    continue;
+                }
+            }
+            captures.push(node.clone());
+        }
+    }
+
+    /// The nodes a successful match of rule `id` over `span` contributes to its parent.
+    pub fn shape(
+        &self,
+        id: RuleId,
+        name: &str,
+        span: Span,
+        children: &[ParseNode],
+    ) -> Vec<ParseNode> {
+        let node = |children: Vec<ParseNode>| ParseNode::NonTerminal {
+            name: name.to_string(),
+            span,
+            children,
+        };
+        match self.node(id) {
+            NodeShape::Discard => vec![],
+            NodeShape::Hide => children.to_vec(),
+            NodeShape::Token => vec![node(vec![])],
+            NodeShape::Pinned => vec![node(children.to_vec())],
+            NodeShape::Keep => match children {
+                [child @ ParseNode::NonTerminal { .. }] if self.collapse_chains => {
+                    vec![child.clone()]
+                }
+                _ => vec![node(children.to_vec())],
+            },
+        }
+    }
+}
+
+impl<'a> PegBuilder<'a> {
+    pub fn new(base: &'a str) -> Self {
+        Self {
+            base,
+            imports: vec![],
+            overlays: vec![],
+            options: GrammarOptions::default(),
+        }
+    }
+
+    /// Makes every rule of `grammar` available as `namespace.Rule`. References between the
+    /// imported rules are qualified the same way, so they keep pointing at each other.
+    pub fn import(mut self, namespace: &'a str, grammar: &'a str) -> Self {
+        self.imports.push((namespace, grammar));
+        self
+    }
+
+    /// Adds the rules of `grammar`, replacing any rule of the same name from the base, the
+    /// imports or earlier overlays. Imported rules are replaced by defining `namespace.Rule`.
+    pub fn overlay(mut self, grammar: &'a str) -> Self {
+        self.overlays.push(grammar);
+        self
+    }
+
+    /// Options applied to each text on its own; duplicates across texts are always allowed.
+    pub fn with_options(mut self, options: GrammarOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Combines the texts and compiles the result. Positions in a [`GrammarError::Parse`] or
+    /// `TransformError::DuplicateRule` refer to whichever text contains the error.
+    pub fn build(&self, start: &str) -> GrammarResult<Peg> {
+        let mut combined = Fragment::default();
+        for (namespace, grammar) in &self.imports {
+            combined.layer(self.fragment(grammar)?.namespaced(namespace));
+        }
+        combined.layer(self.fragment(self.base)?);
+        for grammar in &self.overlays {
+            combined.layer(self.fragment(grammar)?);
+        }
+        combined.build(start).map_err(GrammarError::from)
+    }
+
+    fn fragment(&self, grammar: &str) -> GrammarResult<Fragment> {
+        let tokens = Peg::parse_grammar(grammar)?;
+        Transformer {
+            source: grammar,
+            options: self.options,
+        }
+        .fragment(&tokens)
+        .map_err(GrammarError::from)
+    }
+}
+
+impl<'a> ParseContext<'a> {
+    pub fn new(peg: &'a Peg) -> Self {
+        Self {
+            peg,
+            memo: MemoTable::default(),
+            mode: MatchMode::default(),
+            farthest: FarthestFailure::default(),
+            stack: Vec::new(),
+            negation_depth: 0,
+            growing: Vec::new(),
+        }
+    }
+
+    /// Sets how much of the input later parses must match (all of it by default).
+    pub fn with_mode(mut self, mode: MatchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn mode(&self) -> MatchMode {
+        self.mode
+    }
+
+    pub fn parse(&mut self, input: &str) -> ParseResult {
+        let peg = self.peg;
+        self.parse_rule(&peg.start, input)
+    }
+
+    /// Parses `input` starting from `rule` instead of the grammar's start rule.
+    pub fn parse_rule(&mut self, rule: &str, input: &str) -> ParseResult {
+        // Clear the memoization cache before starting a new parse
+        self.memo.reset(self.peg.rules.len(), input.len());
+        self.farthest = FarthestFailure::default();
+        self.stack.clear();
+        self.negation_depth = 0;
+        self.growing.clear();
+
+        let result = Rule::parse_named(self, rule, input, 0, 0);
+        let parsed = match result.outcome.as_ref() {
+            Err(_) if self.farthest.expected.is_empty() => result,
+            Err(e) => {
+                let mut error = self.farthest_failure(input, rule);
+                error.cause = Some(Box::new(e.clone()));
+                Parsed::err(0, error)
+            }
+            Ok(_) if self.mode == MatchMode::Full && result.end < input.len() => {
+                let at = result.end;
+                self.record_expected(at, Expected::EndOfInput);
+                Parsed::err(
+                    0,
+                    ParseError {
+                        position: at,
+                        rule_name: rule.to_string(),
+                        error: ErrorKind::UnconsumedInput { at },
+                        cause: Some(Box::new(self.farthest_failure(input, rule))),
+                    },
+                )
+            }
+            Ok(_) => result,
+        };
+        ParseResult::new(parsed, input)
+    }
+
+    /// Records that `rule` failed to match at `position`, if that is at least as far as
+    /// any failure seen so far.
+    pub(crate) fn record_failure(&mut self, position: usize, rule: &'a Rule) {
+        if self.negation_depth == 0 && position >= self.farthest.position {
+            self.record_expected(position, Expected::Terminal(rule));
+        }
+    }
+
+    fn record_expected(&mut self, position: usize, expected: Expected<'a>) {
+        if position < self.farthest.position {
+            return;
+        }
+        if position > self.farthest.position || self.farthest.expected.is_empty() {
+            self.farthest.position = position;
+            self.farthest.expected.clear();
+            self.farthest.rule_stack.clone_from(&self.stack);
+        }
+        let seen = self.farthest.expected.iter().any(|e| match (e, &expected) {
+            (Expected::Terminal(a), Expected::Terminal(b)) => std::ptr::eq(*a, *b),
+            (Expected::EndOfInput, Expected::EndOfInput) => true,
+            _ => false,
+        });
+        if !seen {
+            self.farthest.expected.push(expected);
+        }
+    }
+
+    /// An error pointing at the farthest failure, listing what was expected there.
+    fn farthest_failure(&self, input: &str, start: &str) -> ParseError {
+        let position = self.farthest.position;
+        let (line, column) = line_column(input, position);
+        let rule_stack: Vec<String> = self
+            .farthest
+            .rule_stack
+            .iter()
+            .map(|&id| self.peg.rules.name(id).to_string())
+            .collect();
+        // Sorted and without duplicates, as different terminals can print the same
+        let expected: BTreeSet<String> = self
+            .farthest
+            .expected
+            .iter()
+            .map(|expected| match expected {
+                Expected::Terminal(rule) => rule.error_description(),
+                Expected::EndOfInput => END_OF_INPUT.to_string(),
+            })
+            .collect();
+        ParseError {
+            position,
+            rule_name: rule_stack
+                .last()
+                .cloned()
+                .unwrap_or_else(|| start.to_string()),
+            error: ErrorKind::Expected {
+                expected: expected.into_iter().collect(),
+                found: input.get(position..).and_then(|rest| rest.chars().next()),
+                line,
+                column,
+                rule_stack,
+            },
+            cause: None,
         }
     }
 }
@@ -107,18 +563,44 @@ impl RuleBuilder {
                 Rule::NonTerminal(CHAR.to_string()),
             ])),
             Rule::Literal(quote.to_string()),
+            self.optional(Rule::NonTerminal(IGNORE_CASE.to_string())),
             spacing.clone(),
         ])
     }
 }
 
 fn define_operators(gb: &mut RuleBuilder) {
+    let end_of_line = gb.add_rule(
+        EOL,
+        gb.choice(vec![
+            Rule::Literal("\r\n".to_string()),
+            Rule::Literal("\n".to_string()),
+            Rule::Literal("\r".to_string()),
+        ]),
+    );
+
+    // `# ...` and `// ...` run to the end of the line, `/* ... */` may span lines.
+    let line_comment = gb.seq(vec![
+        gb.choice(vec![
+            Rule::Literal("#".to_string()),
+            Rule::Literal("//".to_string()),
+        ]),
+        gb.zero_or_more(gb.seq(vec![gb.not(end_of_line.clone()), Rule::Any])),
+    ]);
+    let block_comment = gb.seq(vec![
+        Rule::Literal("/*".to_string()),
+        gb.zero_or_more(gb.seq(vec![gb.not(Rule::Literal("*/".to_string())), Rule::Any])),
+        Rule::Literal("*/".to_string()),
+    ]);
+    let comment = gb.add_rule(COMMENT, gb.choice(vec![line_comment, block_comment]));
+
     let spacing = gb.add_rule(
         SPACING,
         gb.zero_or_more(gb.choice(vec![
             Rule::Literal(" ".to_string()),
-            Rule::Literal("\n".to_string()),
-            Rule::Literal("\r\n".to_string()),
+            Rule::Literal("\t".to_string()),
+            end_of_line,
+            comment,
         ])),
     );
 
@@ -149,13 +631,22 @@ fn define_character_rules(gb: &mut RuleBuilder) {
     let char_rule = gb.choice(vec![
         gb.seq(vec![
             Rule::Literal("\\".to_string()),
-            Rule::create_character_class(&["n", "r", "t", "'", "\"", "[", "]", "\\"]),
+            Rule::create_character_class(&['n', 'r', 't', '\'', '"', '[', ']', '^', '\\']),
         ]),
         gb.seq(vec![
             Rule::Literal("\\".to_string()),
-            gb.one_or_more(Rule::Range("0".to_string(), "9".to_string())),
+            gb.one_or_more(Rule::range('0', '9')),
             Rule::Literal(";".to_string()),
         ]),
+        gb.seq(vec![
+            Rule::Literal("\\u{".to_string()),
+            gb.one_or_more(gb.choice(vec![
+                Rule::range('0', '9'),
+                Rule::range('a', 'f'),
+                Rule::range('A', 'F'),
+            ])),
+            Rule::Literal("}".to_string()),
+        ]),
         gb.seq(vec![gb.not(Rule::Literal("\\".to_string())), Rule::Any]),
     ]);
     gb.add_rule(CHAR, char_rule);
@@ -165,6 +656,8 @@ fn define_character_rules(gb: &mut RuleBuilder) {
         gb.seq(vec![
             Rule::NonTerminal(CHAR.to_string()),
             Rule::Literal("-".to_string()),
+            // `[a-]` is 'a' and '-', not a range ending in ']'.
+            gb.not(Rule::Literal("]".to_string())),
             Rule::NonTerminal(CHAR.to_string()),
         ]),
     );
@@ -232,9 +725,22 @@ fn define_nonterminal_rules(gb: &mut RuleBuilder) {
 
 fn define_grammar_rules(gb: &mut RuleBuilder) {
     let spacing = Rule::NonTerminal(SPACING.to_string());
+    // `~`, `@` or `^` in front of a definition; an empty node when there is none.
+    let annotation = gb.add_rule(
+        ANNOTATION,
+        gb.optional(gb.seq(vec![
+            gb.choice(vec![
+                Rule::Literal("~".to_string()),
+                Rule::Literal("@".to_string()),
+                Rule::Literal("^".to_string()),
+            ]),
+            spacing.clone(),
+        ])),
+    );
     let definition = gb.add_rule(
         DEF,
         gb.seq(vec![
+            annotation,
             Rule::NonTerminal(IDENT.to_string()),
             Rule::NonTerminal(ARROW.to_string()),
             Rule::NonTerminal(EXPR.to_string()),
@@ -252,26 +758,48 @@ fn define_grammar_rules(gb: &mut RuleBuilder) {
 }
 
 fn define_identifier_rule(gb: &mut RuleBuilder, spacing: &Rule) -> Rule {
+    let start = gb.choice(vec![
+        Rule::range('a', 'z'),
+        Rule::range('A', 'Z'),
+        Rule::Literal("_".to_string()),
+    ]);
+    let rest = gb.zero_or_more(gb.choice(vec![
+        Rule::range('a', 'z'),
+        Rule::range('A', 'Z'),
+        Rule::Literal("_".to_string()),
+        Rule::range('0', '9'),
+    ]));
+    // `morph.Vowel`: a rule from a grammar imported under a namespace.
+    let qualifier = gb.seq(vec![
+        Rule::Literal(".".to_string()),
+        start.clone(),
+        rest.clone(),
+    ]);
     gb.add_rule(
         IDENT,
         gb.seq(vec![
-            gb.choice(vec![
-                Rule::Range("a".to_string(), "z".to_string()),
-                Rule::Range("A".to_string(), "Z".to_string()),
-                Rule::Literal("_".to_string()),
-            ]),
-            gb.zero_or_more(gb.choice(vec![
-                Rule::Range("a".to_string(), "z".to_string()),
-                Rule::Range("A".to_string(), "Z".to_string()),
-                Rule::Literal("_".to_string()),
-                Rule::Range("0".to_string(), "9".to_string()),
-            ])),
+            start,
+            rest,
+            gb.zero_or_more(qualifier),
             spacing.clone(),
         ]),
     )
 }
 
 fn define_literal_rule(gb: &mut RuleBuilder, spacing: &Rule) -> Rule {
+    // `'hoi'i` / `[aeiou]i`: the flag must not run into an identifier (`'a'ii` is `'a' ii`).
+    gb.add_rule(
+        IGNORE_CASE,
+        gb.seq(vec![
+            Rule::Literal("i".to_string()),
+            gb.not(gb.choice(vec![
+                Rule::range('a', 'z'),
+                Rule::range('A', 'Z'),
+                Rule::Literal("_".to_string()),
+                Rule::range('0', '9'),
+            ])),
+        ]),
+    );
     let single_quoted = gb.build_quoted_literal("'", spacing);
     let double_quoted = gb.build_quoted_literal("\"", spacing);
 
@@ -280,15 +808,18 @@ fn define_literal_rule(gb: &mut RuleBuilder, spacing: &Rule) -> Rule {
 
 fn define_class_rule(gb: &mut RuleBuilder) -> Rule {
     let spacing = Rule::NonTerminal(SPACING.to_string());
+    let negate = gb.add_rule(NEGATE, Rule::Literal("^".to_string()));
     gb.add_rule(
         CLASS,
         gb.seq(vec![
             Rule::Literal("[".to_string()),
+            gb.optional(negate),
             gb.zero_or_more(gb.seq(vec![
                 gb.not(Rule::Literal("]".to_string())),
                 Rule::NonTerminal(CLASS_MEMBER.to_string()),
             ])),
             Rule::Literal("]".to_string()),
+            gb.optional(Rule::NonTerminal(IGNORE_CASE.to_string())),
             spacing.clone(),
         ]),
     )
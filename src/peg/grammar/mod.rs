@@ -4,4 +4,6 @@ mod errors;
 mod types;
 
 pub use self::constants::*;
-pub use self::types::{MemoMap, Peg};
+pub use self::errors::GrammarError;
+pub(crate) use self::types::Shaping;
+pub use self::types::{GrammarOptions, MatchMode, ParseContext, Peg, PegBuilder, TreeShape};
@@ -1,28 +1,192 @@
-use crate::peg::parsing::ParseResult;
-use crate::peg::rule::Rule;
-use std::cell::RefCell;
-use std::collections::HashMap;
+use crate::peg::parsing::Parsed;
+use crate::peg::rule::{Rule, RuleId, RuleTable};
 use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
-/// Memo key: (rule_name, position). Using rustc_hash for faster hashing in the hot path.
-pub type MemoMap = rustc_hash::FxHashMap<(String, usize), ParseResult>;
+/// Dense memo table keyed by (rule_id, position). Each input position gets a column with one
+/// slot per rule, allocated the first time a result at that position is stored.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MemoTable {
+    rule_count: usize,
+    columns: Vec<Option<Box<[Option<MemoEntry>]>>>,
+}
 
+/// A memoized result. `negated` marks results computed inside a `!` predicate, where
+/// failures are not recorded for error reports.
+#[derive(Clone, Debug)]
+pub(crate) struct MemoEntry {
+    pub parsed: Parsed,
+    pub negated: bool,
+}
+
+/// A compiled grammar. Immutable once built, so a single instance (e.g. behind an `Arc`)
+/// can serve any number of threads; per-parse state lives in [`ParseContext`].
 #[derive(Clone, Debug)]
 pub struct Peg {
-    pub rules: Arc<HashMap<String, Rule>>,
+    pub rules: Arc<RuleTable>,
     pub start: String,
-    pub memo: RefCell<MemoMap>,
+    pub(crate) shaping: Shaping,
+}
+
+/// Which nodes a parse keeps in its tree, see [`Peg::with_shape`]. Rule patterns are a rule
+/// name, `prefix*` or `*suffix` (e.g. `_*` for every rule whose name starts with `_`).
+/// Annotations in the grammar text (`~`, `@`, `^`) take precedence over the patterns.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TreeShape {
+    pub(crate) merge_terminals: bool,
+    pub(crate) collapse_chains: bool,
+    pub(crate) hidden: Vec<String>,
+    pub(crate) discarded: Vec<String>,
+}
+
+/// What happens to the node of one rule.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum NodeShape {
+    #[default]
+    Keep,
+    /// Replaced by its children.
+    Hide,
+    /// Left out together with everything below it.
+    Discard,
+    /// A leaf covering the match, without children.
+    Token,
+    /// Kept even where the shape would collapse it.
+    Pinned,
+}
+
+/// A [`TreeShape`] resolved against the rule table.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Shaping {
+    pub merge_terminals: bool,
+    pub collapse_chains: bool,
+    /// Indexed by rule id; empty when every rule keeps its node.
+    pub nodes: Arc<[NodeShape]>,
+}
+
+/// Settings for building a [`Peg`] from grammar text, see [`Peg::with_options`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GrammarOptions {
+    overrides: bool,
+}
+
+impl GrammarOptions {
+    /// Lets a later definition of a rule replace an earlier one instead of failing with
+    /// `TransformError::DuplicateRule`, for layering local changes over a base grammar.
+    pub fn with_overrides(mut self, overrides: bool) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    pub fn overrides(&self) -> bool {
+        self.overrides
+    }
+}
+
+/// Builds a [`Peg`] from a base grammar plus other grammar texts: imports, whose rules are
+/// available under a namespace (`morph.Vowel`), and overlays, which add or replace rules.
+/// References are checked once everything is combined, so the base may use rules that only
+/// an overlay or import defines.
+#[derive(Clone, Debug)]
+pub struct PegBuilder<'a> {
+    pub(crate) base: &'a str,
+    pub(crate) imports: Vec<(&'a str, &'a str)>,
+    pub(crate) overlays: Vec<&'a str>,
+    pub(crate) options: GrammarOptions,
+}
+
+/// How much of the input a parse has to match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    /// Succeed when the rule matches a prefix of the input; see `ParseResult::end`.
+    Prefix,
+    /// Fail with `ErrorKind::UnconsumedInput` unless the rule matches the whole input.
+    #[default]
+    Full,
+}
+
+/// Per-parse session: borrows a [`Peg`] and owns the memo table.
+/// Reusing one context for several inputs keeps the memo table's allocation around.
+#[derive(Debug)]
+pub struct ParseContext<'a> {
+    pub peg: &'a Peg,
+    pub(crate) memo: MemoTable,
+    pub(crate) mode: MatchMode,
+    pub(crate) farthest: FarthestFailure<'a>,
+    /// Rules currently being parsed, innermost last.
+    pub(crate) stack: Vec<RuleId>,
+    /// Number of enclosing `!` predicates; failures inside them are expected and not recorded.
+    pub(crate) negation_depth: usize,
+    /// Positions at which a left-recursion leader is growing its seed, innermost last.
+    pub(crate) growing: Vec<usize>,
+}
+
+/// The farthest position at which a terminal failed to match, and what was tried there.
+/// Descriptions are only formatted when the error is built.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FarthestFailure<'a> {
+    pub position: usize,
+    pub expected: Vec<Expected<'a>>,
+    pub rule_stack: Vec<RuleId>,
+}
+
+/// Something the parser tried at the farthest failure.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Expected<'a> {
+    /// A terminal of the grammar
+    Terminal(&'a Rule),
+    EndOfInput,
+}
+
+// `Peg` is advertised as shareable between threads; keep it that way.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Peg>();
+};
+
+impl MemoTable {
+    /// Empties the table and sizes it for `rule_count` rules over an input of `input_len` bytes.
+    /// Columns are cleared in place, so their allocations carry over to the next parse.
+    pub fn reset(&mut self, rule_count: usize, input_len: usize) {
+        self.rule_count = rule_count;
+        for column in &mut self.columns {
+            match column {
+                Some(entries) if entries.len() == rule_count => entries.fill(None),
+                _ => *column = None,
+            }
+        }
+        if self.columns.len() < input_len + 1 {
+            self.columns.resize_with(input_len + 1, || None);
+        }
+    }
+
+    pub fn get(&self, id: RuleId, position: usize) -> Option<&MemoEntry> {
+        self.columns.get(position)?.as_ref()?[id].as_ref()
+    }
+
+    pub fn insert(&mut self, id: RuleId, position: usize, parsed: Parsed, negated: bool) {
+        if position >= self.columns.len() {
+            self.columns.resize_with(position + 1, || None);
+        }
+        let rule_count = self.rule_count;
+        let column =
+            self.columns[position].get_or_insert_with(|| vec![None; rule_count].into_boxed_slice());
+        column[id] = Some(MemoEntry { parsed, negated });
+    }
 }
 
 impl Display for Peg {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut rules: Vec<_> = self
-            .rules
-            .iter()
-            .map(|(name, expr)| format!("\t{} <- {}", name, expr))
-            .collect();
-        rules.sort();
-        write!(f, "PEG ({}) {{\n{}\n}}", self.start, rules.join("\n"))
+        writeln!(f, "PEG ({}) {{", self.start)?;
+        // The table is ordered by name, so the output is stable.
+        for (id, (name, expr)) in self.rules.iter().enumerate() {
+            for line in self.rules.doc(id).into_iter().flat_map(str::lines) {
+                writeln!(f, "\t# {}", line)?;
+            }
+            match self.rules.annotation(id) {
+                Some(annotation) => writeln!(f, "\t{}{} <- {}", annotation.marker(), name, expr)?,
+                None => writeln!(f, "\t{} <- {}", name, expr)?,
+            }
+        }
+        write!(f, "}}")
     }
 }
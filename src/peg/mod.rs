@@ -1,4 +1,7 @@
+pub mod diagnostic;
+pub mod fold;
 pub mod grammar;
 pub mod parsing;
 pub mod rule;
 pub mod transformer;
+pub mod tree;
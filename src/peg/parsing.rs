@@ -2,13 +2,15 @@ use serde::Serialize;
 use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
-#[derive(Clone, Debug, Serialize)] // Add Serialize
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)] // Add Serialize
 pub struct Span(pub usize, pub usize);
 
 #[derive(Clone, Debug, Serialize)] // Add Serialize
 #[serde(tag = "type")] // Use tagged enum representation for clarity in JSON
 pub enum ParseNode {
-    Terminal { span: Span },
+    Terminal {
+        span: Span,
+    },
     NonTerminal {
         name: String,
         span: Span,
@@ -16,24 +18,115 @@ pub enum ParseNode {
     },
 }
 
-/// Parse result: (cost, position, payload). Payload is Arc-wrapped so cloning is cheap (memo cache).
-#[derive(Clone)]
-pub struct ParseResult(pub u32, pub usize, pub Arc<Result<Vec<ParseNode>, ParseError>>);
+/// What a rule produced at one position: where the match ended (the start position on
+/// failure) and the nodes or error. This is what the memo table stores; the payload is
+/// Arc-wrapped so cloning is cheap.
+#[derive(Clone, Debug)]
+pub(crate) struct Parsed {
+    pub end: usize,
+    pub outcome: Arc<Result<Vec<ParseNode>, ParseError>>,
+}
 
-impl std::fmt::Debug for ParseResult {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("ParseResult")
-            .field(&self.0)
-            .field(&self.1)
-            .field(&self.2)
-            .finish()
+impl Parsed {
+    pub fn ok(end: usize, nodes: Vec<ParseNode>) -> Self {
+        Self {
+            end,
+            outcome: Arc::new(Ok(nodes)),
+        }
+    }
+
+    pub fn err(position: usize, error: ParseError) -> Self {
+        Self {
+            end: position,
+            outcome: Arc::new(Err(error)),
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.outcome.is_ok()
+    }
+}
+
+/// The outcome of parsing an input: the parse tree, or the error explaining why the input
+/// does not match.
+///
+/// ```rust
+/// use camxes_rs::peg::grammar::Peg;
+///
+/// let peg = Peg::new("number", "number <- [0-9]+").unwrap();
+/// let result = peg.parse("42");
+/// assert!(result.is_complete());
+/// assert_eq!(result.end(), 2);
+/// assert_eq!(result.tree().map(<[_]>::len), Some(1));
+/// assert!(peg.parse("4x").error().is_some());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParseResult {
+    end: usize,
+    complete: bool,
+    outcome: Arc<Result<Vec<ParseNode>, ParseError>>,
+}
+
+impl ParseResult {
+    pub(crate) fn new(parsed: Parsed, input: &str) -> Self {
+        Self {
+            complete: parsed.is_ok() && parsed.end == input.len(),
+            end: parsed.end,
+            outcome: parsed.outcome,
+        }
+    }
+
+    /// Byte offset where the match ended; 0 if parsing failed.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.outcome.is_ok()
+    }
+
+    /// True when parsing succeeded and consumed the whole input. Only differs from
+    /// [`is_ok`](Self::is_ok) for parses in `MatchMode::Prefix`.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// The top-level nodes (normally a single node for the start rule), if parsing succeeded.
+    pub fn tree(&self) -> Option<&[ParseNode]> {
+        self.outcome.as_ref().as_ref().ok().map(Vec::as_slice)
+    }
+
+    /// The node of the rule the parse started from, if parsing succeeded.
+    pub fn root(&self) -> Option<&ParseNode> {
+        self.tree().and_then(<[_]>::first)
+    }
+
+    pub fn error(&self) -> Option<&ParseError> {
+        self.outcome.as_ref().as_ref().err()
+    }
+
+    pub fn as_result(&self) -> Result<&[ParseNode], &ParseError> {
+        match self.outcome.as_ref() {
+            Ok(nodes) => Ok(nodes),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn into_result(self) -> Result<Vec<ParseNode>, ParseError> {
+        Arc::try_unwrap(self.outcome).unwrap_or_else(|shared| (*shared).clone())
+    }
+}
+
+impl From<ParseResult> for Result<Vec<ParseNode>, ParseError> {
+    fn from(result: ParseResult) -> Self {
+        result.into_result()
     }
 }
 
 #[derive(Serialize)]
 struct SerializableParseResult<'a> {
-    cost: u32,
-    position: usize,
+    end: usize,
+    complete: bool,
     #[serde(flatten)]
     result: &'a Result<Vec<ParseNode>, ParseError>,
 }
@@ -44,15 +137,14 @@ impl Serialize for ParseResult {
         S: serde::Serializer,
     {
         SerializableParseResult {
-            cost: self.0,
-            position: self.1,
-            result: self.2.as_ref(),
+            end: self.end,
+            complete: self.complete,
+            result: self.outcome.as_ref(),
         }
         .serialize(serializer)
     }
 }
 
-
 #[derive(Clone, Debug, Serialize)]
 #[serde(tag = "kind")]
 pub enum ErrorKind {
@@ -61,6 +153,38 @@ pub enum ErrorKind {
     NotDidMatch(Vec<ParseNode>),
     NonTerminalDoesNotMatch,
     NonTerminalDoesNotExist(String),
+    /// The farthest point the parser reached, with every terminal that was tried there.
+    /// Failures inside a `!` predicate are left out, but a rule tried there first still
+    /// reports them when it is reached again outside the predicate:
+    ///
+    /// ```rust
+    /// use camxes_rs::peg::grammar::Peg;
+    /// use camxes_rs::peg::parsing::ErrorKind;
+    ///
+    /// let peg = Peg::new("s", "s <- !x 'a' / x\nx <- 'b' 'c'").unwrap();
+    /// let error = peg.parse("bd").error().unwrap().clone();
+    /// assert_eq!(error.position, 1);
+    /// assert!(matches!(error.error, ErrorKind::Expected { ref expected, column: 2, .. }
+    ///     if expected == &["'c'"]));
+    /// ```
+    Expected {
+        expected: Vec<String>,
+        /// The character at the failure position, `None` at end of input.
+        found: Option<char>,
+        line: usize,
+        column: usize,
+        /// Rules being parsed when the farthest failure happened, outermost first.
+        rule_stack: Vec<String>,
+    },
+    /// The rule matched, but only up to byte `at`; the farthest failure is the error's cause.
+    UnconsumedInput {
+        at: usize,
+    },
+    /// A [`Fold`](crate::peg::fold::Fold) rejected the node spanning up to byte `end`.
+    ActionFailed {
+        message: String,
+        end: usize,
+    },
 }
 
 /// Parse error with lazy line/column: only `position` is stored; use `line_column(input)` when needed.
@@ -127,6 +251,27 @@ impl Display for ErrorKind {
             ErrorKind::NonTerminalDoesNotExist(name) => {
                 write!(f, "Non-terminal rule '{}' does not exist", name)
             }
+            ErrorKind::Expected {
+                expected,
+                found,
+                line,
+                column,
+                ..
+            } => {
+                match expected.as_slice() {
+                    [single] => write!(f, "Expected {}", single)?,
+                    _ => write!(f, "Expected one of {}", expected.join(", "))?,
+                }
+                match found {
+                    Some(c) => write!(f, " but found {:?}", c)?,
+                    None => write!(f, " but found end of input")?,
+                }
+                write!(f, " at line {}, column {}", line, column)
+            }
+            ErrorKind::UnconsumedInput { at } => {
+                write!(f, "Input not fully consumed, match ends at {}", at)
+            }
+            ErrorKind::ActionFailed { message, .. } => write!(f, "Action failed: {}", message),
         }
     }
 }
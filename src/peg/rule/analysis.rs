@@ -0,0 +1,277 @@
+use super::table::RuleId;
+use super::types::Rule;
+
+/// How a rule takes part in left recursion, decided when the grammar is compiled.
+///
+/// Left-recursive rules parse left-associatively, whether they call themselves directly or
+/// through other rules:
+///
+/// ```rust
+/// use camxes_rs::peg::grammar::{GrammarError, Peg};
+/// use camxes_rs::peg::transformer::TransformError;
+///
+/// let direct = "expr <- expr '-' num / num\nnum <- [0-9]";
+/// let indirect = "expr <- sum / num\nsum <- expr '-' num\nnum <- [0-9]";
+/// for grammar in [direct, indirect] {
+///     let peg = Peg::new("expr", grammar).unwrap();
+///     let input = "1-2-3";
+///     let result = peg.parse(input);
+///     // Down the left edge, each `expr` holds one operand fewer
+///     let mut exprs = vec![];
+///     let mut node = result.root();
+///     while let Some(n) = node {
+///         if n.name() == Some("expr") {
+///             exprs.push(n.text(input));
+///         }
+///         node = n.children().first();
+///     }
+///     assert_eq!(exprs, ["1-2-3", "1-2", "1"]);
+/// }
+///
+/// // Without any one of these rules the other two still form a cycle, so none can lead
+/// let grammar = "a <- b 'x' / c 'y' / 'z'\nb <- a 'p' / c 'r'\nc <- a 'q' / b 's'";
+/// assert!(matches!(
+///     Peg::new("a", grammar),
+///     Err(GrammarError::Transform(TransformError::UnsupportedLeftRecursion { .. }))
+/// ));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Recursion {
+    /// Not left-recursive: memoized normally.
+    #[default]
+    None,
+    /// Breaks a left-recursive cycle: parsed by growing a seed on the memo table.
+    Leader,
+    /// In a left-recursive cycle led by another rule: not memoized where a seed is growing,
+    /// since its result there changes with the seed.
+    Member,
+}
+
+/// Which rules can match without consuming input, computed to a fixpoint.
+pub(crate) fn nullable_rules(rules: &[Rule]) -> Vec<bool> {
+    let mut nullable = vec![false; rules.len()];
+    loop {
+        let mut changed = false;
+        for (id, rule) in rules.iter().enumerate() {
+            if !nullable[id] && rule.is_nullable(&nullable) {
+                nullable[id] = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            return nullable;
+        }
+    }
+}
+
+/// Groups of rules that can call each other without consuming input, each paired with the
+/// rule chosen to lead it. `Err` lists a group in which no single rule is part of every
+/// cycle, which seed growing cannot handle.
+pub(crate) fn left_recursion(
+    rules: &[Rule],
+    nullable: &[bool],
+) -> Result<Vec<(RuleId, Vec<RuleId>)>, Vec<RuleId>> {
+    let graph: Vec<Vec<RuleId>> = rules
+        .iter()
+        .map(|rule| {
+            let mut calls = vec![];
+            rule.left_calls(nullable, &mut calls);
+            calls.sort_unstable();
+            calls.dedup();
+            calls
+        })
+        .collect();
+
+    let mut groups = vec![];
+    for component in strongly_connected(&graph) {
+        let recursive = component.len() > 1 || graph[component[0]].contains(&component[0]);
+        if !recursive {
+            continue;
+        }
+        let leader = component
+            .iter()
+            .copied()
+            .find(|&candidate| is_acyclic_without(&graph, &component, candidate))
+            .ok_or_else(|| component.clone())?;
+        groups.push((leader, component));
+    }
+    Ok(groups)
+}
+
+impl Rule {
+    pub(crate) fn is_nullable(&self, nullable: &[bool]) -> bool {
+        match self {
+            Rule::Empty => true,
+            Rule::Literal(text) | Rule::InsensitiveLiteral(text) => text.is_empty(),
+            Rule::Any | Rule::Class(_) | Rule::NegatedClass(_) | Rule::NonTerminal(_) => false,
+            Rule::Ref(id, _) => nullable[*id],
+            Rule::ZeroOrMore(_) | Rule::Optional(_) | Rule::And(_) | Rule::Not(_) => true,
+            Rule::Group(expr) | Rule::OneOrMore(expr) => expr.is_nullable(nullable),
+            Rule::Choice(choices) => choices.iter().any(|c| c.is_nullable(nullable)),
+            Rule::Sequence(sequence) => sequence.iter().all(|s| s.is_nullable(nullable)),
+        }
+    }
+
+    /// Collects every rule this expression refers to.
+    pub(crate) fn references(&self, refs: &mut Vec<RuleId>) {
+        match self {
+            Rule::Ref(id, _) => refs.push(*id),
+            Rule::Group(expr)
+            | Rule::ZeroOrMore(expr)
+            | Rule::OneOrMore(expr)
+            | Rule::Optional(expr)
+            | Rule::And(expr)
+            | Rule::Not(expr) => expr.references(refs),
+            Rule::Choice(rules) | Rule::Sequence(rules) => {
+                for rule in rules {
+                    rule.references(refs);
+                }
+            }
+            Rule::Empty
+            | Rule::Any
+            | Rule::Literal(_)
+            | Rule::InsensitiveLiteral(_)
+            | Rule::NonTerminal(_)
+            | Rule::Class(_)
+            | Rule::NegatedClass(_) => {}
+        }
+    }
+
+    /// Collects the rules this expression may invoke at its own start position.
+    pub(crate) fn left_calls(&self, nullable: &[bool], calls: &mut Vec<RuleId>) {
+        match self {
+            Rule::Ref(id, _) => calls.push(*id),
+            Rule::Group(expr)
+            | Rule::ZeroOrMore(expr)
+            | Rule::OneOrMore(expr)
+            | Rule::Optional(expr)
+            | Rule::And(expr)
+            | Rule::Not(expr) => expr.left_calls(nullable, calls),
+            Rule::Choice(choices) => {
+                for choice in choices {
+                    choice.left_calls(nullable, calls);
+                }
+            }
+            Rule::Sequence(sequence) => {
+                for item in sequence {
+                    item.left_calls(nullable, calls);
+                    if !item.is_nullable(nullable) {
+                        break;
+                    }
+                }
+            }
+            Rule::Empty
+            | Rule::Any
+            | Rule::Literal(_)
+            | Rule::InsensitiveLiteral(_)
+            | Rule::NonTerminal(_)
+            | Rule::Class(_)
+            | Rule::NegatedClass(_) => {}
+        }
+    }
+}
+
+/// Tarjan's algorithm; components come out with their members sorted.
+fn strongly_connected(graph: &[Vec<RuleId>]) -> Vec<Vec<RuleId>> {
+    struct State<'g> {
+        graph: &'g [Vec<RuleId>],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<RuleId>,
+        next: usize,
+        components: Vec<Vec<RuleId>>,
+    }
+
+    fn visit(state: &mut State<'_>, node: RuleId) {
+        state.index[node] = Some(state.next);
+        state.low[node] = state.next;
+        state.next += 1;
+        state.stack.push(node);
+        state.on_stack[node] = true;
+
+        for &next in &state.graph[node] {
+            match state.index[next] {
+                None => {
+                    visit(state, next);
+                    state.low[node] = state.low[node].min(state.low[next]);
+                }
+                Some(index) if state.on_stack[next] => {
+                    state.low[node] = state.low[node].min(index);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(state.low[node]) == state.index[node] {
+            let mut component = vec![];
+            while let Some(member) = state.stack.pop() {
+                state.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            component.sort_unstable();
+            state.components.push(component);
+        }
+    }
+
+    let mut state = State {
+        graph,
+        index: vec![None; graph.len()],
+        low: vec![0; graph.len()],
+        on_stack: vec![false; graph.len()],
+        stack: vec![],
+        next: 0,
+        components: vec![],
+    };
+    for node in 0..graph.len() {
+        if state.index[node].is_none() {
+            visit(&mut state, node);
+        }
+    }
+    state.components
+}
+
+/// True when removing `removed` leaves no cycle among the other members of `component`.
+fn is_acyclic_without(graph: &[Vec<RuleId>], component: &[RuleId], removed: RuleId) -> bool {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        Unvisited,
+        Active,
+        Done,
+    }
+
+    fn has_cycle(
+        graph: &[Vec<RuleId>],
+        component: &[RuleId],
+        removed: RuleId,
+        marks: &mut [Mark],
+        node: RuleId,
+    ) -> bool {
+        marks[node] = Mark::Active;
+        for &next in &graph[node] {
+            if next == removed || component.binary_search(&next).is_err() {
+                continue;
+            }
+            let cyclic = match marks[next] {
+                Mark::Active => true,
+                Mark::Unvisited => has_cycle(graph, component, removed, marks, next),
+                Mark::Done => false,
+            };
+            if cyclic {
+                return true;
+            }
+        }
+        marks[node] = Mark::Done;
+        false
+    }
+
+    let mut marks = vec![Mark::Unvisited; graph.len()];
+    component.iter().all(|&node| {
+        node == removed
+            || marks[node] != Mark::Unvisited
+            || !has_cycle(graph, component, removed, &mut marks, node)
+    })
+}
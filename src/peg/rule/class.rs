@@ -0,0 +1,109 @@
+use std::cmp::Ordering;
+
+/// A character class compiled at grammar-build time: an ASCII bitmap plus a sorted table of
+/// merged non-ASCII ranges, so membership is a single bit test or binary search.
+/// Case-insensitive classes have the other case of every member folded in when built.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CharClass {
+    ascii: u128,
+    ranges: Vec<(char, char)>,
+    /// Members as written in the grammar (single characters are `(c, c)`), for display.
+    items: Vec<(char, char)>,
+    ignore_case: bool,
+}
+
+impl CharClass {
+    pub fn new(items: Vec<(char, char)>, ignore_case: bool) -> Self {
+        let mut members: Vec<(char, char)> = items.clone();
+        if ignore_case {
+            let folded: Vec<char> = items
+                .iter()
+                .flat_map(|&(start, end)| start..=end)
+                .flat_map(|c| {
+                    single_char(c.to_lowercase())
+                        .into_iter()
+                        .chain(single_char(c.to_uppercase()))
+                })
+                .collect();
+            members.extend(folded.into_iter().map(|c| (c, c)));
+        }
+
+        let mut ascii = 0u128;
+        let mut ranges = vec![];
+        for (start, end) in members {
+            if start > end {
+                continue;
+            }
+            for c in start..=end.min('\u{7f}') {
+                ascii |= 1 << (c as u32);
+            }
+            if end > '\u{7f}' {
+                ranges.push((start.max('\u{80}'), end));
+            }
+        }
+        Self {
+            ascii,
+            ranges: merge(ranges),
+            items,
+            ignore_case,
+        }
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        if c.is_ascii() {
+            self.ascii & (1 << (c as u32)) != 0
+        } else {
+            self.ranges
+                .binary_search_by(|&(start, end)| {
+                    if end < c {
+                        Ordering::Less
+                    } else if start > c {
+                        Ordering::Greater
+                    } else {
+                        Ordering::Equal
+                    }
+                })
+                .is_ok()
+        }
+    }
+
+    pub fn items(&self) -> &[(char, char)] {
+        &self.items
+    }
+
+    pub fn ignore_case(&self) -> bool {
+        self.ignore_case
+    }
+
+    /// Byte length of the character at `position` if it is (or, when `negated`, is not) a member.
+    pub fn match_at(&self, input: &str, position: usize, negated: bool) -> Option<usize> {
+        match input.as_bytes().get(position) {
+            None => None,
+            Some(&b) if b.is_ascii() => (self.contains(b as char) != negated).then_some(1),
+            Some(_) => {
+                let c = input[position..].chars().next()?;
+                (self.contains(c) != negated).then(|| c.len_utf8())
+            }
+        }
+    }
+}
+
+fn single_char(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    let c = chars.next()?;
+    chars.next().is_none().then_some(c)
+}
+
+/// Sorts ranges and merges overlapping or adjacent ones.
+fn merge(mut ranges: Vec<(char, char)>) -> Vec<(char, char)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(char, char)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if (last.1 as u32).saturating_add(1) >= start as u32 => {
+                last.1 = last.1.max(end);
+            }
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
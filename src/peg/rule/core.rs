@@ -1,33 +1,41 @@
+use super::analysis::Recursion;
+use super::table::RuleId;
 use super::types::Rule;
-use crate::peg::grammar::Peg;
-use crate::peg::parsing::{ErrorKind, ParseError, ParseNode, ParseResult, Span};
+use crate::peg::grammar::ParseContext;
+use crate::peg::parsing::{ErrorKind, ParseError, ParseNode, Parsed, Span};
 use log::{debug, log_enabled, Level};
 use std::sync::Arc;
 
 impl Rule {
-    pub fn parse(&self, peg: &Peg, input: &str, position: usize, depth: usize) -> ParseResult {
+    pub(crate) fn parse<'a>(
+        &'a self,
+        ctx: &mut ParseContext<'a>,
+        input: &str,
+        position: usize,
+        depth: usize,
+    ) -> Parsed {
         match self {
-            Rule::Empty => ParseResult(1, position, Arc::new(Ok(vec![]))),
+            Rule::Empty => Parsed::ok(position, vec![]),
 
             Rule::Any => {
-                if position < input.len() {
-                    ParseResult(
-                        1,
-                        position + 1,
-                        Arc::new(Ok(vec![ParseNode::Terminal {
-                            span: Span(position, position + 1),
-                        }])),
+                if let Some(c) = input[position..].chars().next() {
+                    let end = position + c.len_utf8();
+                    Parsed::ok(
+                        end,
+                        vec![ParseNode::Terminal {
+                            span: Span(position, end),
+                        }],
                     )
                 } else {
-                    ParseResult(
-                        1,
+                    ctx.record_failure(position, self);
+                    Parsed::err(
                         position,
-                        Arc::new(Err(ParseError {
+                        ParseError {
                             position,
                             rule_name: self.error_description(),
                             error: ErrorKind::UnexpectedEndOfInput,
                             cause: None,
-                        })),
+                        },
                     )
                 }
             }
@@ -39,117 +47,33 @@ impl Rule {
                 } else {
                     input[position..].starts_with(pattern)
                 };
-                if matched {
-                    let len = pattern.len();
-                    ParseResult(
-                        1,
-                        position + len,
-                        Arc::new(Ok(vec![ParseNode::Terminal {
-                            span: Span(position, position + len),
-                        }])),
-                    )
-                } else {
-                    ParseResult(
-                        1,
-                        position,
-                        Arc::new(Err(ParseError {
-                            position,
-                            rule_name: self.error_description(),
-                            error: ErrorKind::ExpressionDoesNotMatch,
-                            cause: None,
-                        })),
-                    )
-                }
+                self.terminal(ctx, position, matched.then(|| position + pattern.len()))
             }
 
-            Rule::NonTerminal(name) => {
-                let key = (name.clone(), position);
-
-                if let Some(cached_result) = peg.memo.borrow().get(&key) {
-                    if log_enabled!(Level::Debug) {
-                        debug!(
-                            "{}cache hit {name} @ {position} -> {}",
-                            "│".repeat(depth),
-                            cached_result.1
-                        );
-                    }
-                    return cached_result.clone();
-                }
-
-                if log_enabled!(Level::Debug) {
-                    debug!("{}parsing {name} @ {position}", "│".repeat(depth));
-                }
-
-                let rule = match peg.rules.get(name) {
-                    Some(r) => r,
-                    None => {
-                        let err = ParseError {
-                            position,
-                            rule_name: self.error_description(),
-                            error: ErrorKind::NonTerminalDoesNotExist(name.clone()),
-                            cause: None,
-                        };
-                        let res = ParseResult(1, position, Arc::new(Err(err)));
-                        peg.memo.borrow_mut().insert(key, res.clone());
-                        return res;
-                    }
-                };
-
-                let result = match rule.parse(peg, input, position, depth + 1) {
-                    ParseResult(cost, new_pos, ref payload) => match payload.as_ref() {
-                        Ok(matches) => ParseResult(
-                            cost,
-                            new_pos,
-                            Arc::new(Ok(vec![ParseNode::NonTerminal {
-                                name: name.clone(),
-                                span: Span(position, new_pos),
-                                children: matches.clone(),
-                            }])),
-                        ),
-                        Err(inner) => ParseResult(
-                            cost,
-                            position,
-                            Arc::new(Err(ParseError {
-                                position,
-                                rule_name: self.error_description(),
-                                error: ErrorKind::NonTerminalDoesNotMatch,
-                                cause: Some(Box::new(inner.clone())),
-                            })),
-                        ),
-                    },
-                };
+            Rule::InsensitiveLiteral(pattern) => {
+                let end = match_ignore_case(&input[position..], pattern).map(|len| position + len);
+                self.terminal(ctx, position, end)
+            }
 
-                if log_enabled!(Level::Debug) {
-                    debug!(
-                        "{}└{} {} @ {} -> {}",
-                        "│".repeat(depth),
-                        if result.2.is_ok() { "ok" } else { "err" },
-                        name,
-                        position,
-                        result.1
-                    );
-                }
+            Rule::NonTerminal(name) => Self::parse_named(ctx, name, input, position, depth),
 
-                peg.memo.borrow_mut().insert(key, result.clone());
-                result
-            }
+            Rule::Ref(id, name) => Self::parse_nonterminal(ctx, *id, name, input, position, depth),
 
             Rule::Choice(choices) => {
                 for choice in choices {
-                    let res = choice.parse(peg, input, position, depth);
-                    if res.2.is_ok() {
+                    let res = choice.parse(ctx, input, position, depth);
+                    if res.is_ok() {
                         return res;
                     }
                 }
-                ParseResult(
-                    1,
+                Parsed::err(
                     position,
-                    Arc::new(Err(ParseError {
+                    ParseError {
                         position,
                         rule_name: self.error_description(),
                         error: ErrorKind::ExpressionDoesNotMatch,
                         cause: None,
-                    })),
+                    },
                 )
             }
 
@@ -158,147 +82,374 @@ impl Rule {
                 let mut pos = position;
 
                 for expr in sequence {
-                    let res = expr.parse(peg, input, pos, depth);
-                    match res.2.as_ref() {
+                    let res = expr.parse(ctx, input, pos, depth);
+                    match res.outcome.as_ref() {
                         Ok(m) => {
-                            pos = res.1;
-                            captures.extend(m.iter().cloned());
+                            pos = res.end;
+                            ctx.peg.shaping.append(&mut captures, m);
                         }
-                        Err(e) => {
-                            return ParseResult(1, position, Arc::new(Err(e.clone())));
+                        Err(_) => {
+                            return Parsed {
+                                end: position,
+                                outcome: res.outcome,
+                            };
                         }
                     }
                 }
-                ParseResult(1, pos, Arc::new(Ok(captures)))
+                Parsed::ok(pos, captures)
             }
 
-            Rule::ZeroOrMore(expr) => {
+            Rule::ZeroOrMore(expr) | Rule::OneOrMore(expr) => {
                 let mut captures = Vec::with_capacity(8);
                 let mut pos = position;
+                let mut matched = false;
 
                 loop {
-                    let res = expr.parse(peg, input, pos, depth);
-                    match res.2.as_ref() {
+                    let res = expr.parse(ctx, input, pos, depth);
+                    match res.outcome.as_ref() {
                         Ok(m) => {
-                            pos = res.1;
-                            captures.extend(m.iter().cloned());
+                            pos = res.end;
+                            matched = true;
+                            ctx.peg.shaping.append(&mut captures, m);
+                        }
+                        Err(_) if !matched && matches!(self, Rule::OneOrMore(_)) => {
+                            return Parsed {
+                                end: position,
+                                outcome: res.outcome,
+                            };
                         }
                         Err(_) => break,
                     }
                 }
-                ParseResult(1, pos, Arc::new(Ok(captures)))
+                Parsed::ok(pos, captures)
             }
 
-            Rule::OneOrMore(expr) => Rule::Sequence(vec![
-                Rule::Group(expr.clone()),
-                Rule::ZeroOrMore(expr.clone()),
-            ])
-            .parse(peg, input, position, depth),
-
             Rule::Optional(expr) => {
-                let res = expr.parse(peg, input, position, depth);
-                if res.2.is_ok() {
-                    ParseResult(1, res.1, res.2)
+                let res = expr.parse(ctx, input, position, depth);
+                if res.is_ok() {
+                    res
                 } else {
-                    ParseResult(1, position, Arc::new(Ok(vec![])))
+                    Parsed::ok(position, vec![])
                 }
             }
 
             Rule::And(expr) => {
-                let res = expr.parse(peg, input, position, depth);
-                if res.2.is_ok() {
-                    ParseResult(1, position, Arc::new(Ok(vec![])))
+                let res = expr.parse(ctx, input, position, depth);
+                if res.is_ok() {
+                    Parsed::ok(position, vec![])
                 } else {
-                    let e = res.2.as_ref().clone().err().unwrap();
-                    ParseResult(1, position, Arc::new(Err(e)))
+                    Parsed {
+                        end: position,
+                        outcome: res.outcome,
+                    }
                 }
             }
 
             Rule::Not(expr) => {
-                let res = expr.parse(peg, input, position, depth);
-                match res.2.as_ref() {
-                    Ok(m) => ParseResult(
-                        1,
+                ctx.negation_depth += 1;
+                let res = expr.parse(ctx, input, position, depth);
+                ctx.negation_depth -= 1;
+                match res.outcome.as_ref() {
+                    Ok(m) => Parsed::err(
                         position,
-                        Arc::new(Err(ParseError {
+                        ParseError {
                             position,
                             rule_name: self.error_description(),
                             error: ErrorKind::NotDidMatch(m.clone()),
                             cause: None,
-                        })),
+                        },
                     ),
-                    Err(_) => ParseResult(1, position, Arc::new(Ok(vec![]))),
+                    Err(_) => Parsed::ok(position, vec![]),
                 }
             }
 
-            Rule::Group(expr) => expr.parse(peg, input, position, depth),
-
-            Rule::Range(start, end) => {
-                if position < input.len() {
-                    let c = input[position..].chars().next().unwrap();
-                    if start.chars().next().unwrap() <= c && c <= end.chars().next().unwrap() {
-                        ParseResult(
-                            1,
-                            position + 1,
-                            Arc::new(Ok(vec![ParseNode::Terminal {
-                                span: Span(position, position + 1),
-                            }])),
-                        )
-                    } else {
-                        ParseResult(
-                            1,
-                            position,
-                            Arc::new(Err(ParseError {
-                                position,
-                                rule_name: self.error_description(),
-                                error: ErrorKind::ExpressionDoesNotMatch,
-                                cause: None,
-                            })),
-                        )
-                    }
-                } else {
-                    ParseResult(
-                        1,
+            Rule::Group(expr) => expr.parse(ctx, input, position, depth),
+
+            Rule::Class(class) => {
+                let end = class
+                    .match_at(input, position, false)
+                    .map(|len| position + len);
+                self.terminal(ctx, position, end)
+            }
+
+            Rule::NegatedClass(class) => {
+                let end = class
+                    .match_at(input, position, true)
+                    .map(|len| position + len);
+                self.terminal(ctx, position, end)
+            }
+        }
+    }
+
+    /// Result of a terminal that matched `position..end`, or failed at `position` if `end` is `None`.
+    fn terminal<'a>(
+        &'a self,
+        ctx: &mut ParseContext<'a>,
+        position: usize,
+        end: Option<usize>,
+    ) -> Parsed {
+        match end {
+            Some(end) => Parsed::ok(
+                end,
+                vec![ParseNode::Terminal {
+                    span: Span(position, end),
+                }],
+            ),
+            None => {
+                ctx.record_failure(position, self);
+                Parsed::err(
+                    position,
+                    ParseError {
                         position,
-                        Arc::new(Err(ParseError {
-                            position,
-                            rule_name: self.error_description(),
-                            error: ErrorKind::ExpressionDoesNotMatch,
-                            cause: None,
-                        })),
-                    )
+                        rule_name: self.error_description(),
+                        error: ErrorKind::ExpressionDoesNotMatch,
+                        cause: None,
+                    },
+                )
+            }
+        }
+    }
+
+    /// Parses the rule called `name`, or fails if the grammar has none.
+    pub(crate) fn parse_named(
+        ctx: &mut ParseContext<'_>,
+        name: &str,
+        input: &str,
+        position: usize,
+        depth: usize,
+    ) -> Parsed {
+        match ctx.peg.rules.id(name) {
+            Some(id) => Self::parse_nonterminal(ctx, id, name, input, position, depth),
+            None => Parsed::err(
+                position,
+                ParseError {
+                    position,
+                    rule_name: name.to_string(),
+                    error: ErrorKind::NonTerminalDoesNotExist(name.to_string()),
+                    cause: None,
+                },
+            ),
+        }
+    }
+
+    fn parse_nonterminal(
+        ctx: &mut ParseContext<'_>,
+        id: RuleId,
+        name: &str,
+        input: &str,
+        position: usize,
+        depth: usize,
+    ) -> Parsed {
+        let recursion = ctx.peg.rules.recursion(id);
+        let memoize = recursion != Recursion::Member || !ctx.growing.contains(&position);
+        if memoize {
+            // An entry computed inside `!` recorded no failures, so outside one it is parsed
+            // again for the error report to see them.
+            match ctx.memo.get(id, position) {
+                Some(cached) if !cached.negated || ctx.negation_depth > 0 => {
+                    if log_enabled!(Level::Debug) {
+                        debug!(
+                            "{}cache hit {name} @ {position} -> {}",
+                            "│".repeat(depth),
+                            cached.parsed.end
+                        );
+                    }
+                    return cached.parsed.clone();
                 }
+                _ => {}
             }
+        }
 
-            Rule::Class(symbols) => {
-                // Longest match first: sort by length descending
-                let mut syms: Vec<&String> = symbols.iter().collect();
-                syms.sort_by(|a, b| b.len().cmp(&a.len()));
-                let matched = syms
-                    .into_iter()
-                    .find(|s| input[position..].starts_with(s.as_str()));
-                if let Some(symbol) = matched {
-                    let len = symbol.len();
-                    ParseResult(
-                        1,
-                        position + len,
-                        Arc::new(Ok(vec![ParseNode::Terminal {
-                            span: Span(position, position + len),
-                        }])),
-                    )
-                } else {
-                    ParseResult(
-                        1,
-                        position,
-                        Arc::new(Err(ParseError {
-                            position,
-                            rule_name: self.error_description(),
-                            error: ErrorKind::ExpressionDoesNotMatch,
-                            cause: None,
-                        })),
-                    )
+        if log_enabled!(Level::Debug) {
+            debug!("{}parsing {name} @ {position}", "│".repeat(depth));
+        }
+
+        let result = match recursion {
+            Recursion::Leader => Self::grow_seed(ctx, id, name, input, position, depth),
+            Recursion::None | Recursion::Member => {
+                Self::expand(ctx, id, name, input, position, depth)
+            }
+        };
+
+        if log_enabled!(Level::Debug) {
+            debug!(
+                "{}└{} {} @ {} -> {}",
+                "│".repeat(depth),
+                if result.is_ok() { "ok" } else { "err" },
+                name,
+                position,
+                result.end
+            );
+        }
+
+        if memoize {
+            ctx.memo
+                .insert(id, position, result.clone(), ctx.negation_depth > 0);
+        }
+        result
+    }
+
+    /// Parses a left-recursion leader: the memo entry starts as a failure so the recursive
+    /// call falls through to a non-recursive alternative, then the body is re-parsed with each
+    /// longer match as the memoized result until it stops growing.
+    ///
+    /// While growing, the memo holds a placeholder for a seed that is a single node, so each
+    /// round builds only the new part of the tree; the previous seed is then moved into the
+    /// placeholder's place rather than copied.
+    fn grow_seed(
+        ctx: &mut ParseContext<'_>,
+        id: RuleId,
+        name: &str,
+        input: &str,
+        position: usize,
+        depth: usize,
+    ) -> Parsed {
+        let mut result = Parsed::err(
+            position,
+            ParseError {
+                position,
+                rule_name: name.to_string(),
+                error: ErrorKind::NonTerminalDoesNotMatch,
+                cause: None,
+            },
+        );
+        ctx.memo
+            .insert(id, position, result.clone(), ctx.negation_depth > 0);
+        ctx.growing.push(position);
+
+        loop {
+            let mut next = Self::expand(ctx, id, name, input, position, depth);
+            if !next.is_ok() {
+                if !result.is_ok() {
+                    result = next;
                 }
+                break;
+            }
+            if result.is_ok() && next.end <= result.end {
+                break;
             }
+            plant_seed(&mut next, result, position);
+            ctx.memo.insert(
+                id,
+                position,
+                placeholder_for(&next, position),
+                ctx.negation_depth > 0,
+            );
+            result = next;
+        }
+
+        ctx.growing.pop();
+        result
+    }
+
+    /// Parses the body of rule `id` and wraps it in a `NonTerminal` node, shaped by the
+    /// grammar's `TreeShape`, or an error.
+    fn expand(
+        ctx: &mut ParseContext<'_>,
+        id: RuleId,
+        name: &str,
+        input: &str,
+        position: usize,
+        depth: usize,
+    ) -> Parsed {
+        let peg = ctx.peg;
+        ctx.stack.push(id);
+        let parsed = peg.rules.rule(id).parse(ctx, input, position, depth + 1);
+        ctx.stack.pop();
+        match parsed.outcome.as_ref() {
+            Ok(matches) => Parsed::ok(
+                parsed.end,
+                peg.shaping
+                    .shape(id, name, Span(position, parsed.end), matches),
+            ),
+            Err(inner) => Parsed::err(
+                position,
+                ParseError {
+                    position,
+                    rule_name: name.to_string(),
+                    error: ErrorKind::NonTerminalDoesNotMatch,
+                    cause: Some(Box::new(inner.clone())),
+                },
+            ),
+        }
+    }
+}
+
+/// Byte length of the prefix of `input` that equals `pattern` ignoring case.
+fn match_ignore_case(input: &str, pattern: &str) -> Option<usize> {
+    if pattern.is_ascii() {
+        let len = pattern.len();
+        let prefix = input.as_bytes().get(..len)?;
+        return prefix
+            .eq_ignore_ascii_case(pattern.as_bytes())
+            .then_some(len);
+    }
+    let mut chars = input.char_indices();
+    for p in pattern.chars() {
+        let (_, c) = chars.next()?;
+        if !(c == p || c.to_lowercase().eq(p.to_lowercase())) {
+            return None;
+        }
+    }
+    Some(chars.next().map_or(input.len(), |(i, _)| i))
+}
+
+/// What the memo answers for a growing seed: a placeholder node with the seed's span when the
+/// seed is a single node, otherwise the seed itself.
+fn placeholder_for(seed: &Parsed, position: usize) -> Parsed {
+    match seed.outcome.as_ref() {
+        Ok(nodes) if matches!(nodes.as_slice(), [ParseNode::NonTerminal { .. }]) => Parsed::ok(
+            seed.end,
+            vec![ParseNode::NonTerminal {
+                // No rule has an empty name
+                name: String::new(),
+                span: Span(position, seed.end),
+                children: vec![],
+            }],
+        ),
+        _ => seed.clone(),
+    }
+}
+
+/// Replaces the placeholders in `grown`, which is not shared yet, with the node of `seed`.
+fn plant_seed(grown: &mut Parsed, seed: Parsed, position: usize) {
+    let Ok(nodes) = Arc::make_mut(&mut grown.outcome) else {
+        return;
+    };
+    let mut found = vec![];
+    placeholders(nodes, position, &mut found);
+    let Some(last) = found.pop() else {
+        return;
+    };
+    let seed = match Arc::try_unwrap(seed.outcome) {
+        Ok(Ok(mut nodes)) => nodes.pop(),
+        Ok(Err(_)) => None,
+        Err(shared) => shared
+            .as_ref()
+            .as_ref()
+            .ok()
+            .and_then(|n| n.last().cloned()),
+    };
+    if let Some(seed) = seed {
+        for node in found {
+            *node = seed.clone();
+        }
+        *last = seed;
+    }
+}
+
+/// Collects the placeholders starting at `position`. Only nodes starting there can contain
+/// one, so the walk stays on the left edge of the tree.
+fn placeholders<'n>(
+    nodes: &'n mut [ParseNode],
+    position: usize,
+    found: &mut Vec<&'n mut ParseNode>,
+) {
+    for node in nodes.iter_mut().take_while(|n| n.span().0 == position) {
+        match node {
+            ParseNode::NonTerminal { name, .. } if name.is_empty() => found.push(node),
+            ParseNode::NonTerminal { children, .. } => placeholders(children, position, found),
+            ParseNode::Terminal { .. } => {}
         }
     }
 }
@@ -1,3 +1,4 @@
+use super::class::CharClass;
 use super::types::Rule;
 use std::fmt::{Display, Formatter};
 
@@ -6,27 +7,13 @@ impl Display for Rule {
         match self {
             Rule::Empty => write!(f, "()"),
             Rule::Any => write!(f, "."),
-            Rule::Literal(text) => write!(
-                f,
-                "'{}'",
-                text.replace('\\', "\\\\")
-                    .replace('\n', "\\n")
-                    .replace('\'', "\\'")
-            ),
-            Rule::NonTerminal(name) => write!(f, "{}", name),
-            Rule::Range(start, end) => write!(f, "[{}-{}]", start, end),
-            Rule::Class(symbols) => write!(
-                f,
-                "[{}]",
-                symbols
-                    .iter()
-                    .map(|s| s
-                        .replace('\\', "\\\\")
-                        .replace('[', "\\[")
-                        .replace(']', "\\]"))
-                    .collect::<Vec<_>>()
-                    .join("")
-            ),
+            Rule::Literal(text) => write!(f, "'{}'", escape(text, &['\''])),
+            Rule::NonTerminal(name) | Rule::Ref(_, name) => write!(f, "{}", name),
+            Rule::InsensitiveLiteral(text) => write!(f, "'{}'i", escape(text, &['\''])),
+            Rule::Class(class) => write!(f, "[{}]{}", class_members(class), case_flag(class)),
+            Rule::NegatedClass(class) => {
+                write!(f, "[^{}]{}", class_members(class), case_flag(class))
+            }
             Rule::Group(expr) => write!(f, "({})", expr),
             Rule::ZeroOrMore(expr) => write!(f, "{}*", expr),
             Rule::OneOrMore(expr) => write!(f, "{}+", expr),
@@ -68,3 +55,50 @@ impl Display for Rule {
         }
     }
 }
+
+const CLASS_SPECIALS: &[char] = &['[', ']', '^'];
+
+fn class_members(class: &CharClass) -> String {
+    // A lone '-' goes first so it cannot be read as part of a range.
+    let mut items = class.items().to_vec();
+    items.sort_by_key(|&(start, end)| (start, end) != ('-', '-'));
+    items
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                escape_char(start, CLASS_SPECIALS)
+            } else {
+                format!(
+                    "{}-{}",
+                    escape_char(start, CLASS_SPECIALS),
+                    escape_char(end, CLASS_SPECIALS)
+                )
+            }
+        })
+        .collect()
+}
+
+fn case_flag(class: &CharClass) -> &'static str {
+    if class.ignore_case() {
+        "i"
+    } else {
+        ""
+    }
+}
+
+/// Escapes text for grammar syntax; `specials` are the delimiters of the surrounding construct.
+fn escape(text: &str, specials: &[char]) -> String {
+    text.chars().map(|c| escape_char(c, specials)).collect()
+}
+
+fn escape_char(c: char, specials: &[char]) -> String {
+    match c {
+        '\\' => "\\\\".to_string(),
+        '\n' => "\\n".to_string(),
+        '\r' => "\\r".to_string(),
+        '\t' => "\\t".to_string(),
+        c if specials.contains(&c) => format!("\\{}", c),
+        c if c.is_control() => format!("\\u{{{:x}}}", c as u32),
+        c => c.to_string(),
+    }
+}
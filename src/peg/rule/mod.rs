@@ -1,5 +1,11 @@
+mod analysis;
+mod class;
 mod core;
 mod display;
+mod table;
 mod types;
 
+pub use self::analysis::Recursion;
+pub use self::class::CharClass;
+pub use self::table::{RuleId, RuleTable};
 pub use self::types::*;
@@ -0,0 +1,171 @@
+use super::analysis::{self, Recursion};
+use super::types::{Annotation, Rule};
+use crate::peg::transformer::TransformError;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Index of a rule in a [`RuleTable`].
+pub type RuleId = usize;
+
+/// Grammar rules compiled into a dense table: every `NonTerminal` reference is resolved to
+/// a `Rule::Ref` carrying the target's index, while names are kept for parse nodes and errors.
+#[derive(Clone, Debug, Default)]
+pub struct RuleTable {
+    names: Vec<String>,
+    rules: Vec<Rule>,
+    docs: Vec<Option<String>>,
+    annotations: Vec<Option<Annotation>>,
+    recursion: Vec<Recursion>,
+    nullable: Vec<bool>,
+    ids: HashMap<String, RuleId>,
+}
+
+impl RuleTable {
+    /// Assigns ids in name order (so the table layout is deterministic) and resolves references.
+    /// References to undefined rules are left as `NonTerminal` and fail at parse time.
+    ///
+    /// Left-recursive rules are detected here: each cycle gets a leader that is parsed by seed
+    /// growing. A cycle that no single rule breaks is rejected with
+    /// [`TransformError::UnsupportedLeftRecursion`].
+    pub fn compile(rules: HashMap<String, Rule>) -> Result<Self, TransformError> {
+        let mut definitions: Vec<_> = rules.into_iter().collect();
+        definitions.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let ids: HashMap<String, RuleId> = definitions
+            .iter()
+            .enumerate()
+            .map(|(id, (name, _))| (name.clone(), id))
+            .collect();
+        let (names, rules): (Vec<String>, Vec<Rule>) = definitions
+            .into_iter()
+            .map(|(name, rule)| {
+                let rule = rule.resolve(&ids);
+                (name, rule)
+            })
+            .unzip();
+
+        let nullable = analysis::nullable_rules(&rules);
+        let mut recursion = vec![Recursion::None; rules.len()];
+        match analysis::left_recursion(&rules, &nullable) {
+            Ok(groups) => {
+                for (leader, members) in groups {
+                    for member in members {
+                        recursion[member] = Recursion::Member;
+                    }
+                    recursion[leader] = Recursion::Leader;
+                }
+            }
+            Err(cycle) => {
+                return Err(TransformError::UnsupportedLeftRecursion {
+                    rules: cycle.iter().map(|&id| (names[id].clone(), None)).collect(),
+                });
+            }
+        }
+
+        let docs = vec![None; ids.len()];
+        let annotations = vec![None; ids.len()];
+        Ok(Self {
+            names,
+            rules,
+            docs,
+            annotations,
+            recursion,
+            nullable,
+            ids,
+        })
+    }
+
+    /// Attaches documentation (from grammar comments) to the rule called `name`.
+    pub fn set_doc(&mut self, name: &str, doc: String) {
+        if let Some(id) = self.id(name) {
+            self.docs[id] = Some(doc);
+        }
+    }
+
+    /// Sets the `~`/`@`/`^` marker of the rule called `name`.
+    pub fn set_annotation(&mut self, name: &str, annotation: Annotation) {
+        if let Some(id) = self.id(name) {
+            self.annotations[id] = Some(annotation);
+        }
+    }
+
+    pub fn id(&self, name: &str) -> Option<RuleId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: RuleId) -> &str {
+        &self.names[id]
+    }
+
+    pub fn rule(&self, id: RuleId) -> &Rule {
+        &self.rules[id]
+    }
+
+    pub fn doc(&self, id: RuleId) -> Option<&str> {
+        self.docs[id].as_deref()
+    }
+
+    pub fn annotation(&self, id: RuleId) -> Option<Annotation> {
+        self.annotations[id]
+    }
+
+    pub fn recursion(&self, id: RuleId) -> Recursion {
+        self.recursion[id]
+    }
+
+    /// True when the rule can succeed without consuming input.
+    pub fn is_nullable(&self, id: RuleId) -> bool {
+        self.nullable[id]
+    }
+
+    /// True when `rule`, written against this table's names, can succeed without consuming input.
+    pub fn can_match_empty(&self, rule: &Rule) -> bool {
+        rule.resolve(&self.ids).is_nullable(&self.nullable)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Rule> {
+        self.id(name).map(|id| self.rule(id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Iterates over `(name, rule)` pairs in id order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Rule)> {
+        self.names.iter().map(String::as_str).zip(self.rules.iter())
+    }
+}
+
+impl Rule {
+    /// Replaces `NonTerminal` names with `Ref`s into the table being compiled.
+    fn resolve(&self, ids: &HashMap<String, RuleId>) -> Rule {
+        let resolve_all = |rules: &[Rule]| rules.iter().map(|r| r.resolve(ids)).collect();
+        let resolve_boxed = |rule: &Arc<Rule>| rule.resolve(ids).boxed();
+        match self {
+            Rule::NonTerminal(name) => match ids.get(name) {
+                Some(&id) => Rule::Ref(id, name.clone()),
+                None => self.clone(),
+            },
+            Rule::Group(expr) => Rule::Group(resolve_boxed(expr)),
+            Rule::ZeroOrMore(expr) => Rule::ZeroOrMore(resolve_boxed(expr)),
+            Rule::OneOrMore(expr) => Rule::OneOrMore(resolve_boxed(expr)),
+            Rule::Optional(expr) => Rule::Optional(resolve_boxed(expr)),
+            Rule::And(expr) => Rule::And(resolve_boxed(expr)),
+            Rule::Not(expr) => Rule::Not(resolve_boxed(expr)),
+            Rule::Choice(choices) => Rule::Choice(resolve_all(choices)),
+            Rule::Sequence(sequence) => Rule::Sequence(resolve_all(sequence)),
+            Rule::Empty
+            | Rule::Any
+            | Rule::Literal(_)
+            | Rule::Ref(..)
+            | Rule::InsensitiveLiteral(_)
+            | Rule::Class(_)
+            | Rule::NegatedClass(_) => self.clone(),
+        }
+    }
+}
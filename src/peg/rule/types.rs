@@ -1,14 +1,21 @@
-use std::collections::HashSet;
+use super::class::CharClass;
+use super::table::RuleId;
 use std::sync::Arc;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Rule {
     Empty,
     Any,
     Literal(String),
     NonTerminal(String),
-    Range(String, String),
-    Class(HashSet<String>),
+    /// A non-terminal resolved by [`RuleTable::compile`](super::RuleTable::compile).
+    Ref(RuleId, String),
+    /// `'hoi'i`: a literal matched without regard to case.
+    InsensitiveLiteral(String),
+    /// `[a-z_]` / `[aeiou]i`: one character from the class.
+    Class(CharClass),
+    /// `[^...]`: one character not in the class.
+    NegatedClass(CharClass),
     Group(Arc<Rule>),
     ZeroOrMore(Arc<Rule>),
     OneOrMore(Arc<Rule>),
@@ -19,20 +26,83 @@ pub enum Rule {
     Sequence(Vec<Rule>),
 }
 
+/// Marker in front of a definition (`~sp <- ...`) that controls the rule's parse node,
+/// whatever [`TreeShape`](crate::peg::grammar::TreeShape) is in effect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Annotation {
+    /// `~`: the rule never produces a node, nor do the rules below it.
+    Silent,
+    /// `@`: the rule produces a leaf node covering the matched text, without children.
+    Token,
+    /// `^`: the rule always produces its node, even where the tree shape would hide,
+    /// discard or collapse it.
+    Keep,
+}
+
+impl Annotation {
+    pub fn from_marker(marker: char) -> Option<Self> {
+        match marker {
+            '~' => Some(Annotation::Silent),
+            '@' => Some(Annotation::Token),
+            '^' => Some(Annotation::Keep),
+            _ => None,
+        }
+    }
+
+    pub fn marker(self) -> char {
+        match self {
+            Annotation::Silent => '~',
+            Annotation::Token => '@',
+            Annotation::Keep => '^',
+        }
+    }
+}
+
 impl Rule {
     pub fn boxed(self) -> Arc<Rule> {
         Arc::new(self)
     }
 
-    pub fn create_character_class(chars: &[&str]) -> Rule {
-        Rule::Class(chars.iter().map(|&c| c.to_string()).collect())
+    pub fn create_character_class(chars: &[char]) -> Rule {
+        Rule::Class(CharClass::new(
+            chars.iter().map(|&c| (c, c)).collect(),
+            false,
+        ))
+    }
+
+    /// `[start-end]`
+    pub fn range(start: char, end: char) -> Rule {
+        Rule::Class(CharClass::new(vec![(start, end)], false))
+    }
+
+    /// Copy of the rule with every `NonTerminal` name passed through `rename`.
+    pub fn rename(&self, rename: &impl Fn(&str) -> String) -> Rule {
+        let rename_all = |rules: &[Rule]| rules.iter().map(|r| r.rename(rename)).collect();
+        let rename_boxed = |rule: &Arc<Rule>| rule.rename(rename).boxed();
+        match self {
+            Rule::NonTerminal(name) => Rule::NonTerminal(rename(name)),
+            Rule::Group(expr) => Rule::Group(rename_boxed(expr)),
+            Rule::ZeroOrMore(expr) => Rule::ZeroOrMore(rename_boxed(expr)),
+            Rule::OneOrMore(expr) => Rule::OneOrMore(rename_boxed(expr)),
+            Rule::Optional(expr) => Rule::Optional(rename_boxed(expr)),
+            Rule::And(expr) => Rule::And(rename_boxed(expr)),
+            Rule::Not(expr) => Rule::Not(rename_boxed(expr)),
+            Rule::Choice(choices) => Rule::Choice(rename_all(choices)),
+            Rule::Sequence(sequence) => Rule::Sequence(rename_all(sequence)),
+            Rule::Empty
+            | Rule::Any
+            | Rule::Literal(_)
+            | Rule::Ref(..)
+            | Rule::InsensitiveLiteral(_)
+            | Rule::Class(_)
+            | Rule::NegatedClass(_) => self.clone(),
+        }
     }
 
     /// Short description for ParseError (avoids storing full Rule in error).
     pub fn error_description(&self) -> String {
         match self {
-            Rule::NonTerminal(n) => n.clone(),
-            Rule::Literal(p) => format!("'{}'", p),
+            Rule::NonTerminal(n) | Rule::Ref(_, n) => n.clone(),
             _ => format!("{}", self),
         }
     }
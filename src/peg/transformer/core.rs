@@ -1,69 +1,198 @@
 use super::errors::TransformError;
-use crate::peg::grammar::Peg;
-use crate::peg::grammar::MemoMap;
+use super::types::Fragment;
+use crate::peg::grammar::{GrammarOptions, Peg, Shaping, TreeShape};
 use crate::peg::grammar::{
-    AND, ARROW, CHAR, CLASS, CLASS_MEMBER, DEF, DOT, EOF, EXPR, IDENT, LITERAL, LPAR, NOT, PLUS,
-    PREFIX, PRIMARY, QUESTION, RANGE, RPAR, SEQUENCE, SLASH, SPACING, STAR, SUFFIX, TEXT,
+    AND, ANNOTATION, ARROW, CHAR, CLASS, CLASS_MEMBER, COMMENT, DEF, DOT, EOF, EXPR, IDENT,
+    IGNORE_CASE, LITERAL, LPAR, NEGATE, NOT, PLUS, PREFIX, PRIMARY, QUESTION, RANGE, RPAR,
+    SEQUENCE, SLASH, SPACING, STAR, SUFFIX, TEXT,
 };
 use crate::peg::parsing::{ParseNode, Span};
-use crate::peg::rule::Rule;
-use std::collections::{HashMap, HashSet};
-use std::cell::RefCell;
+use crate::peg::rule::{Annotation, CharClass, Rule, RuleTable};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 type Result<T> = std::result::Result<T, TransformError>;
 
+/// The rules an expression refers to, with the span of each reference.
+type References = Vec<(String, Span)>;
+
 pub struct Transformer<'a> {
     pub source: &'a str,
+    pub options: GrammarOptions,
 }
 
 impl Transformer<'_> {
     pub fn build(&self, start_rule: &str, cst: Vec<ParseNode>) -> Result<Peg> {
-        match &cst[..] {
-            [ParseNode::NonTerminal { name, children: tokens, .. }] if name == TEXT => Ok(Peg {
-                rules: self.build_grammar_rules(tokens)?,
-                start: start_rule.to_string(),
-                memo: RefCell::new(MemoMap::default()),
+        self.fragment(&cst)?.build(start_rule)
+    }
+
+    /// Reads the definitions and docs of one grammar text without checking references, so
+    /// the result can be combined with other texts first.
+    pub(crate) fn fragment(&self, cst: &[ParseNode]) -> Result<Fragment> {
+        match cst {
+            [ParseNode::NonTerminal {
+                name,
+                children: tokens,
+                ..
+            }] if name == TEXT => Ok(Fragment {
+                docs: self.collect_docs(tokens)?.into_iter().collect(),
+                ..self.definitions(tokens)?
             }),
-            [ParseNode::NonTerminal { name: n, .. }] => Err(TransformError::CstShouldStartWithGrammar(
-                format!("Found '{n}' instead!"),
-            )),
+            [ParseNode::NonTerminal { name: n, .. }] => Err(
+                TransformError::CstShouldStartWithGrammar(format!("Found '{n}' instead!")),
+            ),
             _ => Err(TransformError::CstShouldOnlyHaveOneRoot(
                 "Invalid root structure".into(),
             )),
         }
     }
 
-    fn build_grammar_rules(&self, tokens: &[ParseNode]) -> Result<Arc<HashMap<String, Rule>>> {
-        let (rules, refs) = tokens
+    pub(super) fn build_grammar_rules(
+        &self,
+        tokens: &[ParseNode],
+    ) -> Result<HashMap<String, Rule>> {
+        self.definitions(tokens)?.into_rules()
+    }
+
+    /// Reads the rules and annotations of a grammar text, without docs.
+    fn definitions(&self, tokens: &[ParseNode]) -> Result<Fragment> {
+        let mut fragment = Fragment::default();
+        let mut spans: HashMap<String, Span> = HashMap::new();
+        for parse_node in tokens
             .iter()
             .skip(1)
             .take_while(|t| !matches!(t, ParseNode::NonTerminal { name: n, .. } if n == EOF))
-            .try_fold(
-                (HashMap::new(), HashSet::new()),
-                |(mut rules, mut refs), parse_node| {
-                    let (name, expr, new_refs) = self.process_rule(parse_node)?;
-                    rules.insert(name, expr);
-                    refs.extend(new_refs);
-                    Ok((rules, refs))
+        {
+            let (name, span, expr, refs) = self.process_rule(parse_node)?;
+            if let Some(first) = spans.insert(name.clone(), span) {
+                if !self.options.overrides() {
+                    return Err(TransformError::DuplicateRule {
+                        name,
+                        first,
+                        second: span,
+                    });
+                }
+            }
+            match self.extract_annotation(parse_node)? {
+                Some(annotation) => fragment.annotations.insert(name.clone(), annotation),
+                None => fragment.annotations.remove(&name),
+            };
+            fragment.spans.insert(name.clone(), span);
+            fragment.rules.insert(name, (expr, refs));
+        }
+        Ok(fragment)
+    }
+
+    /// Attaches grammar comments to rules: own-line comments directly above a definition
+    /// (no blank line in between) and comments inside a definition become its doc.
+    fn collect_docs(&self, tokens: &[ParseNode]) -> Result<Vec<(String, String)>> {
+        let mut comments = vec![];
+        for token in tokens {
+            Self::collect_comments(token, &mut comments);
+        }
+        let definitions = tokens
+            .iter()
+            .skip(1)
+            .take_while(|t| !Self::is_token(EOF, t))
+            .map(
+                |definition| match Self::get_tokens(DEF, definition)?.as_slice() {
+                    [_, id, ..] => Ok((self.extract_identifier(id)?, definition.span().0)),
+                    _ => Err(TransformError::WrongNumberOfTokens(
+                        "Definition needs 4 tokens".into(),
+                    )),
                 },
-            )?;
+            )
+            .collect::<Result<Vec<_>>>()?;
 
-        let defined: HashSet<_> = rules.keys().cloned().collect();
-        let undefined: Vec<_> = refs.difference(&defined).cloned().collect();
-        if !undefined.is_empty() {
-            return Err(TransformError::AmbiguousNonTerminal(format!(
-                "Missing rules: [{}]!",
-                undefined.join(", ")
-            )));
+        let mut docs: Vec<Vec<String>> = vec![vec![]; definitions.len()];
+        let mut attached = vec![false; comments.len()];
+        for (i, (_, id_start)) in definitions.iter().enumerate() {
+            let mut next_start = *id_start;
+            let mut leading = vec![];
+            for (c, &(start, end)) in comments.iter().enumerate().rev() {
+                if end > next_start {
+                    continue;
+                }
+                let gap = &self.source[end..next_start];
+                if !gap.trim().is_empty()
+                    || gap.matches('\n').count() > 1
+                    || !self.starts_line(start)
+                {
+                    break;
+                }
+                attached[c] = true;
+                leading.push(Self::comment_text(&self.source[start..end]));
+                next_start = start;
+            }
+            docs[i].extend(leading.into_iter().rev());
+        }
+        for (c, &(start, _)) in comments.iter().enumerate() {
+            if attached[c] {
+                continue;
+            }
+            if let Some(i) = definitions
+                .iter()
+                .rposition(|(_, id_start)| *id_start <= start)
+            {
+                docs[i].push(Self::comment_text(
+                    &self.source[comments[c].0..comments[c].1],
+                ));
+            }
         }
-        Ok(Arc::new(rules))
+
+        Ok(definitions
+            .into_iter()
+            .zip(docs)
+            .filter(|(_, doc)| !doc.is_empty())
+            .map(|((name, _), doc)| (name, doc.join("\n")))
+            .collect())
     }
 
-    fn process_rule(&self, parse_node: &ParseNode) -> Result<(String, Rule, HashSet<String>)> {
-        let [id, arrow, expr] = Self::get_tokens(DEF, parse_node)?.as_slice() else {
+    fn collect_comments(parse_node: &ParseNode, comments: &mut Vec<(usize, usize)>) {
+        if let ParseNode::NonTerminal {
+            name,
+            span,
+            children,
+        } = parse_node
+        {
+            if name == COMMENT {
+                comments.push((span.0, span.1));
+            } else {
+                for child in children {
+                    Self::collect_comments(child, comments);
+                }
+            }
+        }
+    }
+
+    /// True when only whitespace precedes `position` on its line.
+    fn starts_line(&self, position: usize) -> bool {
+        self.source[..position]
+            .rsplit('\n')
+            .next()
+            .is_some_and(|prefix| prefix.trim().is_empty())
+    }
+
+    fn comment_text(raw: &str) -> String {
+        let text = raw
+            .strip_prefix("/*")
+            .and_then(|r| r.strip_suffix("*/"))
+            .or_else(|| raw.strip_prefix("//"))
+            .or_else(|| raw.strip_prefix('#'))
+            .unwrap_or(raw);
+        text.trim()
+            .lines()
+            .map(|line| line.trim().trim_start_matches('*').trim())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Converts one definition into its name, the span of the name, its expression and the
+    /// rules it refers to.
+    fn process_rule(&self, parse_node: &ParseNode) -> Result<(String, Span, Rule, References)> {
+        let [_, id, arrow, expr] = Self::get_tokens(DEF, parse_node)?.as_slice() else {
             return Err(TransformError::WrongNumberOfTokens(
-                "Definition needs 3 tokens".into(),
+                "Definition needs 4 tokens".into(),
             ));
         };
         let name = self.extract_identifier(id)?;
@@ -71,10 +200,23 @@ impl Transformer<'_> {
             return Err(TransformError::UnExpectedToken(ARROW.into()));
         }
         let (expr, refs) = self.convert_rule(expr)?;
-        Ok((name, expr, refs))
+        Ok((name, self.content_span(id), expr, refs))
     }
 
-    fn extract_identifier(&self, parse_node: &ParseNode) -> Result<String> {
+    /// The `~`/`@`/`^` marker in front of a definition, if any.
+    fn extract_annotation(&self, parse_node: &ParseNode) -> Result<Option<Annotation>> {
+        match Self::get_tokens(DEF, parse_node)?.first() {
+            Some(annotation) if Self::is_token(ANNOTATION, annotation) => Ok(self.source
+                [annotation.span().0..]
+                .chars()
+                .next()
+                .and_then(Annotation::from_marker)
+                .filter(|_| !annotation.children().is_empty())),
+            _ => Err(TransformError::UnExpectedToken(ANNOTATION.into())),
+        }
+    }
+
+    pub(super) fn extract_identifier(&self, parse_node: &ParseNode) -> Result<String> {
         let result = Self::get_tokens(IDENT, parse_node)?
             .iter()
             .take_while(|t| !Self::is_token(SPACING, t))
@@ -90,16 +232,16 @@ impl Transformer<'_> {
         }
     }
 
-    fn convert_rule(&self, parse_node: &ParseNode) -> Result<(Rule, HashSet<String>)> {
+    fn convert_rule(&self, parse_node: &ParseNode) -> Result<(Rule, References)> {
         let chunks: Vec<_> = Self::get_tokens(EXPR, parse_node)?.chunks(2).collect();
-        let (exprs, refs): (Vec<_>, HashSet<_>) =
+        let (exprs, refs): (Vec<_>, References) =
             chunks
                 .iter()
-                .try_fold((vec![], HashSet::new()), |(mut exprs, mut refs), chunk| {
+                .try_fold((vec![], vec![]), |(mut exprs, mut refs), chunk| {
                     let (expr, new_refs) = self.convert_sequence(&chunk[0])?;
                     exprs.push(expr);
                     refs.extend(new_refs);
-                    if chunk.get(1).map_or(false, |t| !Self::is_token(SLASH, t)) {
+                    if chunk.get(1).is_some_and(|t| !Self::is_token(SLASH, t)) {
                         return Err(TransformError::UnExpectedToken(SLASH.into()));
                     }
                     Ok((exprs, refs))
@@ -111,11 +253,11 @@ impl Transformer<'_> {
         })
     }
 
-    fn convert_sequence(&self, parse_node: &ParseNode) -> Result<(Rule, HashSet<String>)> {
-        let (exprs, refs): (Vec<_>, HashSet<_>) = Self::get_tokens(SEQUENCE, parse_node)?
+    pub(super) fn convert_sequence(&self, parse_node: &ParseNode) -> Result<(Rule, References)> {
+        let (exprs, refs): (Vec<_>, References) = Self::get_tokens(SEQUENCE, parse_node)?
             .chunks(3)
             .take_while(|c| c.len() == 3)
-            .try_fold((vec![], HashSet::new()), |(mut exprs, mut refs), chunk| {
+            .try_fold((vec![], vec![]), |(mut exprs, mut refs), chunk| {
                 let (mut expr, new_refs) = self.convert_primary(&chunk[1])?;
                 expr = self.apply_suffix(&chunk[2], expr)?;
                 expr = self.apply_prefix(&chunk[0], expr)?;
@@ -153,7 +295,7 @@ impl Transformer<'_> {
         })
     }
 
-    fn convert_primary(&self, parse_node: &ParseNode) -> Result<(Rule, HashSet<String>)> {
+    pub(super) fn convert_primary(&self, parse_node: &ParseNode) -> Result<(Rule, References)> {
         let tokens = Self::get_tokens(PRIMARY, parse_node)?;
         match tokens.as_slice() {
             [open, expr, close] if Self::is_token(LPAR, open) && Self::is_token(RPAR, close) => {
@@ -162,11 +304,14 @@ impl Transformer<'_> {
             [t] => match Self::get_name(t)? {
                 IDENT => {
                     let id = self.extract_identifier(t)?;
-                    Ok((Rule::NonTerminal(id.clone()), HashSet::from([id])))
+                    Ok((
+                        Rule::NonTerminal(id.clone()),
+                        vec![(id, self.content_span(t))],
+                    ))
                 }
-                LITERAL => Ok((self.convert_literal(t)?, HashSet::new())),
-                CLASS => Ok((self.convert_class(t)?, HashSet::new())),
-                DOT => Ok((Rule::Any, HashSet::new())),
+                LITERAL => Ok((self.convert_literal(t)?, vec![])),
+                CLASS => Ok((self.convert_class(t)?, vec![])),
+                DOT => Ok((Rule::Any, vec![])),
                 _ => Err(TransformError::UnExpectedToken("Invalid primary".into())),
             },
             _ => Err(TransformError::WrongNumberOfTokens(
@@ -175,82 +320,129 @@ impl Transformer<'_> {
         }
     }
 
-    fn unescape_char(&self, parse_node: &ParseNode) -> Result<String> {
-        match parse_node {
-            ParseNode::NonTerminal { name, span: Span(s, e), .. } if name == CHAR => Ok(self.source[*s..*e]
-                .replace("\\'", "'")
-                .replace("\\\"", "\"")
-                .replace("\\[", "[")
-                .replace("\\]", "]")
-                .replace("\\\\", "\\")),
-            _ => Err(TransformError::UnExpectedToken(
-                "Invalid char parse_node".into(),
-            )),
-        }
+    /// Decodes one `Char` token: a plain character, `\n`-style escapes, `\u{1F600}` or the
+    /// decimal code point form `\123;`.
+    fn unescape_char(&self, parse_node: &ParseNode) -> Result<char> {
+        let (raw, span) = match parse_node {
+            ParseNode::NonTerminal { name, span, .. } if name == CHAR => {
+                (&self.source[span.0..span.1], *span)
+            }
+            _ => {
+                return Err(TransformError::UnExpectedToken(
+                    "Invalid char parse_node".into(),
+                ))
+            }
+        };
+        let Some(escape) = raw.strip_prefix('\\') else {
+            return raw.chars().next().ok_or_else(|| invalid_escape(raw, span));
+        };
+        let code_point =
+            if let Some(hex) = escape.strip_prefix("u{").and_then(|e| e.strip_suffix('}')) {
+                u32::from_str_radix(hex, 16).ok()
+            } else if let Some(decimal) = escape.strip_suffix(';') {
+                decimal.parse().ok()
+            } else {
+                return match escape {
+                    "n" => Ok('\n'),
+                    "r" => Ok('\r'),
+                    "t" => Ok('\t'),
+                    "'" | "\"" | "[" | "]" | "^" | "\\" => {
+                        Ok(escape.chars().next().unwrap_or_default())
+                    }
+                    _ => Err(invalid_escape(raw, span)),
+                };
+            };
+        code_point
+            .and_then(char::from_u32)
+            .ok_or_else(|| invalid_escape(raw, span))
     }
 
     fn convert_literal(&self, parse_node: &ParseNode) -> Result<Rule> {
         let tokens = Self::get_tokens(LITERAL, parse_node)?;
-        let content =
-            tokens[1..tokens.len() - 2]
-                .iter()
-                .try_fold(String::new(), |mut acc, t| {
-                    acc.push_str(&self.unescape_char(t)?);
-                    Ok(acc)
-                })?;
-        Ok(Rule::Literal(content))
+        let content = tokens.iter().filter(|t| Self::is_token(CHAR, t)).try_fold(
+            String::new(),
+            |mut acc, t| {
+                acc.push(self.unescape_char(t)?);
+                Ok(acc)
+            },
+        )?;
+        Ok(if tokens.iter().any(|t| Self::is_token(IGNORE_CASE, t)) {
+            Rule::InsensitiveLiteral(content)
+        } else {
+            Rule::Literal(content)
+        })
     }
 
     fn convert_class(&self, parse_node: &ParseNode) -> Result<Rule> {
         let tokens = Self::get_tokens(CLASS, parse_node)?;
-        let (parts, symbols) = tokens[1..tokens.len() - 2].iter().try_fold(
-            (vec![], HashSet::new()),
-            |(mut parts, mut symbols), member| {
-                for t in Self::get_tokens(CLASS_MEMBER, member)? {
-                    if Self::is_token(CHAR, t) {
-                        symbols.insert(self.unescape_char(t)?);
-                    } else {
-                        parts.push(self.convert_range(t)?);
-                    }
+        let ignore_case = tokens.iter().any(|t| Self::is_token(IGNORE_CASE, t));
+        let mut items = vec![];
+        for member in tokens.iter().filter(|t| Self::is_token(CLASS_MEMBER, t)) {
+            for t in Self::get_tokens(CLASS_MEMBER, member)? {
+                if Self::is_token(CHAR, t) {
+                    let c = self.unescape_char(t)?;
+                    items.push((c, c));
+                } else {
+                    items.push(self.convert_range(t)?);
                 }
-                Ok((parts, symbols))
-            },
-        )?;
-        let mut all_parts = parts;
-        if !symbols.is_empty() {
-            all_parts.push(Rule::Class(symbols));
+            }
         }
-        Ok(match all_parts.len() {
-            0 => Rule::Empty,
-            1 => all_parts.remove(0),
-            _ => Rule::Choice(all_parts),
+        let class = CharClass::new(items, ignore_case);
+        Ok(if tokens.iter().any(|t| Self::is_token(NEGATE, t)) {
+            Rule::NegatedClass(class)
+        } else {
+            Rule::Class(class)
         })
     }
 
-    fn convert_range(&self, parse_node: &ParseNode) -> Result<Rule> {
+    pub(super) fn convert_range(&self, parse_node: &ParseNode) -> Result<(char, char)> {
         let tokens = Self::get_tokens(RANGE, parse_node)?;
         if tokens.len() != 3 {
             return Err(TransformError::WrongNumberOfTokens(
                 "Range needs 3 tokens".into(),
             ));
         }
-        Ok(Rule::Range(
+        Ok((
             self.unescape_char(&tokens[0])?,
             self.unescape_char(&tokens[2])?,
         ))
     }
 
-    fn get_tokens<'a>(name: &str, parse_node: &'a ParseNode) -> Result<&'a Vec<ParseNode>> {
+    /// The node's span without trailing whitespace and comments.
+    pub(super) fn content_span(&self, parse_node: &ParseNode) -> Span {
+        match parse_node {
+            ParseNode::Terminal { span } => *span,
+            ParseNode::NonTerminal { span, children, .. } => {
+                let end = children
+                    .iter()
+                    .rev()
+                    .filter(|child| !Self::is_token(SPACING, child))
+                    .map(|child| self.content_span(child))
+                    .find(|child| child.1 > child.0)
+                    .map_or(span.1, |child| child.1);
+                Span(span.0, end)
+            }
+        }
+    }
+
+    pub(super) fn get_tokens<'a>(
+        name: &str,
+        parse_node: &'a ParseNode,
+    ) -> Result<&'a Vec<ParseNode>> {
         match parse_node {
-            ParseNode::NonTerminal { name: n, children: tokens, .. } if n == name => Ok(tokens),
-            ParseNode::NonTerminal { name: n, .. } => Err(TransformError::UnExpectedToken(format!(
-                "Expected {name}, got {n}"
-            ))),
+            ParseNode::NonTerminal {
+                name: n,
+                children: tokens,
+                ..
+            } if n == name => Ok(tokens),
+            ParseNode::NonTerminal { name: n, .. } => Err(TransformError::UnExpectedToken(
+                format!("Expected {name}, got {n}"),
+            )),
             _ => Err(TransformError::UnExpectedToken(format!("Expected {name}"))),
         }
     }
 
-    fn is_token(name: &str, parse_node: &ParseNode) -> bool {
+    pub(super) fn is_token(name: &str, parse_node: &ParseNode) -> bool {
         matches!(parse_node, ParseNode::NonTerminal { name: n, .. } if n == name)
     }
 
@@ -263,3 +455,113 @@ impl Transformer<'_> {
         }
     }
 }
+
+impl Fragment {
+    /// Prefixes every rule name, and every reference between these rules, with `namespace.`.
+    pub(crate) fn namespaced(self, namespace: &str) -> Fragment {
+        let qualify = |name: &str| format!("{namespace}.{name}");
+        Fragment {
+            rules: self
+                .rules
+                .into_iter()
+                .map(|(name, (rule, refs))| {
+                    let refs = refs
+                        .into_iter()
+                        .map(|(r, span)| (qualify(&r), span))
+                        .collect();
+                    (qualify(&name), (rule.rename(&qualify), refs))
+                })
+                .collect(),
+            spans: self
+                .spans
+                .into_iter()
+                .map(|(name, span)| (qualify(&name), span))
+                .collect(),
+            docs: self
+                .docs
+                .into_iter()
+                .map(|(name, doc)| (qualify(&name), doc))
+                .collect(),
+            annotations: self
+                .annotations
+                .into_iter()
+                .map(|(name, annotation)| (qualify(&name), annotation))
+                .collect(),
+        }
+    }
+
+    /// Adds the rules of `overlay`, replacing same-named rules (and their docs and annotations)
+    /// in `self`.
+    pub(crate) fn layer(&mut self, overlay: Fragment) {
+        for name in overlay.rules.keys() {
+            self.docs.remove(name);
+            self.annotations.remove(name);
+        }
+        self.rules.extend(overlay.rules);
+        self.spans.extend(overlay.spans);
+        self.docs.extend(overlay.docs);
+        self.annotations.extend(overlay.annotations);
+    }
+
+    /// Checks that every referenced rule is defined.
+    fn into_rules(self) -> Result<HashMap<String, Rule>> {
+        let mut undefined: References = self
+            .rules
+            .values()
+            .flat_map(|(_, refs)| refs.iter())
+            .filter(|(name, _)| !self.rules.contains_key(name))
+            .cloned()
+            .collect();
+        if !undefined.is_empty() {
+            undefined.sort_by_key(|(_, span)| span.0);
+            return Err(TransformError::AmbiguousNonTerminal {
+                references: undefined,
+            });
+        }
+        Ok(self
+            .rules
+            .into_iter()
+            .map(|(name, (expr, _))| (name, expr))
+            .collect())
+    }
+
+    pub(crate) fn build(self, start_rule: &str) -> Result<Peg> {
+        let docs = self.docs.clone();
+        let annotations = self.annotations.clone();
+        let spans = self.spans.clone();
+        let mut rules = RuleTable::compile(self.into_rules()?).map_err(|error| match error {
+            TransformError::UnsupportedLeftRecursion { rules } => {
+                TransformError::UnsupportedLeftRecursion {
+                    rules: rules
+                        .into_iter()
+                        .map(|(name, _)| {
+                            let span = spans.get(&name).copied();
+                            (name, span)
+                        })
+                        .collect(),
+                }
+            }
+            error => error,
+        })?;
+        for (name, doc) in docs {
+            rules.set_doc(&name, doc);
+        }
+        for (name, annotation) in annotations {
+            rules.set_annotation(&name, annotation);
+        }
+        let peg = Peg {
+            rules: Arc::new(rules),
+            start: start_rule.to_string(),
+            shaping: Shaping::default(),
+        };
+        // Resolves the annotations into the default tree shape.
+        Ok(peg.with_shape(TreeShape::default()))
+    }
+}
+
+fn invalid_escape(raw: &str, span: Span) -> TransformError {
+    TransformError::InvalidEscape {
+        escape: raw.to_string(),
+        span,
+    }
+}
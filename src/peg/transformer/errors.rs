@@ -1,3 +1,4 @@
+use crate::peg::parsing::Span;
 use std::fmt::{Display, Formatter};
 
 /// Error types for the transformer module
@@ -9,12 +10,24 @@ pub enum TransformError {
     CstShouldStartWithGrammar(String),
     /// Error when encountering an unexpected parse_node
     UnExpectedToken(String),
-    /// Error when a non-terminal reference is ambiguous
-    AmbiguousNonTerminal(String),
+    /// Error when rules are referenced but not defined; each reference with its span in the
+    /// grammar, in text order
+    AmbiguousNonTerminal { references: Vec<(String, Span)> },
     /// Error when identifier is empty
     EmptyIdentifier,
     /// Error when parse_node count doesn't match expected
     WrongNumberOfTokens(String),
+    /// Error when an escape sequence is unknown or names an invalid code point
+    InvalidEscape { escape: String, span: Span },
+    /// Error when rules are mutually left-recursive with no single rule on every cycle; spans
+    /// are the rules' names in the grammar, when built from grammar text
+    UnsupportedLeftRecursion { rules: Vec<(String, Option<Span>)> },
+    /// Error when a rule is defined twice; spans are the two definitions' names in the grammar
+    DuplicateRule {
+        name: String,
+        first: Span,
+        second: Span,
+    },
 }
 
 impl Display for TransformError {
@@ -27,13 +40,40 @@ impl Display for TransformError {
                 write!(f, "CST should start with grammar: {}", msg)
             }
             TransformError::UnExpectedToken(token) => write!(f, "Unexpected token: {}", token),
-            TransformError::AmbiguousNonTerminal(msg) => {
-                write!(f, "Ambiguous non-terminal reference: {}", msg)
+            TransformError::AmbiguousNonTerminal { references } => {
+                let mut names: Vec<_> = references.iter().map(|(name, _)| name.as_str()).collect();
+                names.sort_unstable();
+                names.dedup();
+                write!(
+                    f,
+                    "Ambiguous non-terminal reference: Missing rules: [{}]!",
+                    names.join(", ")
+                )
             }
             TransformError::EmptyIdentifier => write!(f, "Empty identifier"),
             TransformError::WrongNumberOfTokens(msg) => {
                 write!(f, "Wrong number of tokens: {}", msg)
             }
+            TransformError::InvalidEscape { escape, .. } => {
+                write!(f, "Invalid escape sequence: {}", escape)
+            }
+            TransformError::UnsupportedLeftRecursion { rules } => {
+                let names: Vec<_> = rules.iter().map(|(name, _)| name.as_str()).collect();
+                write!(
+                    f,
+                    "Unsupported left recursion between rules [{}]",
+                    names.join(", ")
+                )
+            }
+            TransformError::DuplicateRule {
+                name,
+                first,
+                second,
+            } => write!(
+                f,
+                "Rule '{}' is defined twice (at bytes {} and {})",
+                name, first.0, second.0
+            ),
         }
     }
 }
@@ -0,0 +1,335 @@
+use super::core::Transformer;
+use super::errors::TransformError;
+use crate::peg::grammar::{
+    CLASS, CLASS_MEMBER, DEF, EOF, EXPR, LPAR, PLUS, PRIMARY, RANGE, SEQUENCE, SLASH, STAR, SUFFIX,
+    TEXT,
+};
+use crate::peg::parsing::{ParseNode, Span};
+use crate::peg::rule::{Rule, RuleTable};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+type Result<T> = std::result::Result<T, TransformError>;
+
+/// A likely mistake found by [`Peg::lint`](crate::peg::grammar::Peg::lint). The grammar still
+/// builds, but part of it cannot behave as written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LintWarning {
+    /// The rule whose definition contains the problem.
+    pub rule: String,
+    /// Where the problem is in the grammar text.
+    pub span: Span,
+    pub kind: LintKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LintKind {
+    /// No other rule refers to this one and it is not the start rule.
+    UnusedRule,
+    /// Referred to by other rules, but none of them can be reached from the start rule.
+    UnreachableRule,
+    /// `*` or `+` applied to an expression that can match empty input, which never terminates.
+    NullableRepetition,
+    /// A choice alternative that can never be tried because an earlier one, at `by`, always
+    /// matches first (e.g. `'a' / 'ab'`).
+    ShadowedAlternative { by: Span },
+    /// The rule is defined again later; this definition, starting at `span`, is discarded.
+    DuplicateDefinition { replaced_by: Span },
+    /// A class range whose first character comes after its last (`[z-a]`), so it matches
+    /// no character.
+    ReversedRange,
+    /// The start rule is not defined; the span is empty, at the start of the grammar text.
+    UndefinedStartRule,
+}
+
+impl Display for LintWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let rule = &self.rule;
+        match self.kind {
+            LintKind::UnusedRule => write!(f, "rule '{rule}' is never used"),
+            LintKind::UnreachableRule => {
+                write!(f, "rule '{rule}' cannot be reached from the start rule")
+            }
+            LintKind::NullableRepetition => write!(
+                f,
+                "repetition in '{rule}' can match empty input and would loop forever"
+            ),
+            LintKind::ShadowedAlternative { .. } => write!(
+                f,
+                "alternative in '{rule}' is never tried: an earlier alternative always matches first"
+            ),
+            LintKind::DuplicateDefinition { .. } => {
+                write!(f, "rule '{rule}' is defined again later; this definition is ignored")
+            }
+            LintKind::ReversedRange => write!(
+                f,
+                "range in '{rule}' is reversed and matches no character"
+            ),
+            LintKind::UndefinedStartRule => write!(f, "start rule '{rule}' is not defined"),
+        }
+    }
+}
+
+impl Transformer<'_> {
+    /// Reports an undefined start rule, unused and unreachable rules, nullable repetitions,
+    /// shadowed alternatives, reversed class ranges and duplicate definitions, in grammar
+    /// text order.
+    pub fn lint(&self, start_rule: &str, cst: &[ParseNode]) -> Result<Vec<LintWarning>> {
+        let [root] = cst else {
+            return Err(TransformError::CstShouldOnlyHaveOneRoot(
+                "Invalid root structure".into(),
+            ));
+        };
+        let tokens = Self::get_tokens(TEXT, root)?;
+        // Duplicates are reported below rather than failing the build.
+        let layered = Transformer {
+            options: self.options.with_overrides(true),
+            ..*self
+        };
+        let table = RuleTable::compile(layered.build_grammar_rules(tokens)?)?;
+        let mut warnings = vec![];
+        if table.id(start_rule).is_none() {
+            warnings.push(LintWarning {
+                rule: start_rule.to_string(),
+                span: Span(0, 0),
+                kind: LintKind::UndefinedStartRule,
+            });
+        }
+
+        let mut definitions: Vec<(String, Span, &ParseNode)> = vec![];
+        for definition in tokens
+            .iter()
+            .skip(1)
+            .take_while(|t| !Self::is_token(EOF, t))
+        {
+            let [_, id, _, expr] = Self::get_tokens(DEF, definition)?.as_slice() else {
+                return Err(TransformError::WrongNumberOfTokens(
+                    "Definition needs 4 tokens".into(),
+                ));
+            };
+            let name = self.extract_identifier(id)?;
+            definitions.push((name, self.content_span(id), expr));
+        }
+
+        let mut last_definition = HashMap::new();
+        for (i, (name, _, _)) in definitions.iter().enumerate() {
+            last_definition.insert(name.as_str(), i);
+        }
+
+        let (used, reachable) = Self::usage(&table, start_rule);
+        for (i, (name, span, expr)) in definitions.iter().enumerate() {
+            // Each body is checked as written, even one a later definition replaces
+            self.lint_expression(&table, name, expr, &mut warnings)?;
+            let last = last_definition[name.as_str()];
+            if last != i {
+                warnings.push(LintWarning {
+                    rule: name.clone(),
+                    span: *span,
+                    kind: LintKind::DuplicateDefinition {
+                        replaced_by: definitions[last].1,
+                    },
+                });
+                continue;
+            }
+            if let Some(id) = table.id(name) {
+                let kind = if !used[id] && name != start_rule {
+                    Some(LintKind::UnusedRule)
+                } else if !reachable[id] {
+                    Some(LintKind::UnreachableRule)
+                } else {
+                    None
+                };
+                if let Some(kind) = kind {
+                    warnings.push(LintWarning {
+                        rule: name.clone(),
+                        span: *span,
+                        kind,
+                    });
+                }
+            }
+        }
+
+        warnings.sort_by_key(|w| w.span.0);
+        Ok(warnings)
+    }
+
+    /// For each rule: whether another rule refers to it, and whether the start rule reaches it.
+    fn usage(table: &RuleTable, start_rule: &str) -> (Vec<bool>, Vec<bool>) {
+        let references: Vec<Vec<usize>> = (0..table.len())
+            .map(|id| {
+                let mut refs = vec![];
+                table.rule(id).references(&mut refs);
+                refs
+            })
+            .collect();
+
+        let mut used = vec![false; table.len()];
+        for (id, refs) in references.iter().enumerate() {
+            for &target in refs {
+                if target != id {
+                    used[target] = true;
+                }
+            }
+        }
+
+        let mut reachable = vec![false; table.len()];
+        let mut pending: Vec<_> = table.id(start_rule).into_iter().collect();
+        while let Some(id) = pending.pop() {
+            if !std::mem::replace(&mut reachable[id], true) {
+                pending.extend(references[id].iter().copied());
+            }
+        }
+        (used, reachable)
+    }
+
+    fn lint_expression(
+        &self,
+        table: &RuleTable,
+        rule: &str,
+        parse_node: &ParseNode,
+        warnings: &mut Vec<LintWarning>,
+    ) -> Result<()> {
+        let mut alternatives: Vec<(Rule, Span)> = vec![];
+        for chunk in Self::get_tokens(EXPR, parse_node)?.chunks(2) {
+            if chunk.get(1).is_some_and(|t| !Self::is_token(SLASH, t)) {
+                return Err(TransformError::UnExpectedToken(SLASH.into()));
+            }
+            let (alternative, _) = self.convert_sequence(&chunk[0])?;
+            let span = self.content_span(&chunk[0]);
+            if let Some((_, by)) = alternatives
+                .iter()
+                .find(|(earlier, _)| shadows(earlier, &alternative))
+            {
+                warnings.push(LintWarning {
+                    rule: rule.to_string(),
+                    span,
+                    kind: LintKind::ShadowedAlternative { by: *by },
+                });
+            }
+            alternatives.push((alternative, span));
+            self.lint_sequence(table, rule, &chunk[0], warnings)?;
+        }
+        Ok(())
+    }
+
+    fn lint_sequence(
+        &self,
+        table: &RuleTable,
+        rule: &str,
+        parse_node: &ParseNode,
+        warnings: &mut Vec<LintWarning>,
+    ) -> Result<()> {
+        for chunk in Self::get_tokens(SEQUENCE, parse_node)?.chunks(3) {
+            let [_, primary, suffix] = chunk else {
+                continue;
+            };
+            let repeated = Self::get_tokens(SUFFIX, suffix)?
+                .first()
+                .is_some_and(|t| Self::is_token(STAR, t) || Self::is_token(PLUS, t));
+            if repeated {
+                let (expr, _) = self.convert_primary(primary)?;
+                if table.can_match_empty(&expr) {
+                    let start = self.content_span(primary).0;
+                    let end = self.content_span(suffix).1;
+                    warnings.push(LintWarning {
+                        rule: rule.to_string(),
+                        span: Span(start, end),
+                        kind: LintKind::NullableRepetition,
+                    });
+                }
+            }
+            match Self::get_tokens(PRIMARY, primary)?.as_slice() {
+                [open, expr, _] if Self::is_token(LPAR, open) => {
+                    self.lint_expression(table, rule, expr, warnings)?;
+                }
+                [class] if Self::is_token(CLASS, class) => {
+                    self.lint_class(rule, class, warnings)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn lint_class(
+        &self,
+        rule: &str,
+        parse_node: &ParseNode,
+        warnings: &mut Vec<LintWarning>,
+    ) -> Result<()> {
+        for member in Self::get_tokens(CLASS, parse_node)?
+            .iter()
+            .filter(|t| Self::is_token(CLASS_MEMBER, t))
+        {
+            for range in Self::get_tokens(CLASS_MEMBER, member)?
+                .iter()
+                .filter(|t| Self::is_token(RANGE, t))
+            {
+                let (first, last) = self.convert_range(range)?;
+                if first > last {
+                    warnings.push(LintWarning {
+                        rule: rule.to_string(),
+                        span: self.content_span(range),
+                        kind: LintKind::ReversedRange,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// True when `earlier` succeeds on every input `later` could match, so an ordered choice
+/// never gets to `later`. Conservative: only recognises a few common shapes.
+fn shadows(earlier: &Rule, later: &Rule) -> bool {
+    if earlier == later || always_succeeds(earlier) {
+        return true;
+    }
+    let prefix = required_prefix(later);
+    let Some(first) = prefix.chars().next() else {
+        return false;
+    };
+    match earlier {
+        Rule::Group(expr) => shadows(expr, later),
+        Rule::Literal(text) => prefix.starts_with(text.as_str()),
+        Rule::InsensitiveLiteral(text) => prefix.to_lowercase().starts_with(&text.to_lowercase()),
+        Rule::Any => true,
+        Rule::Class(class) => class.contains(first),
+        Rule::NegatedClass(class) => !class.contains(first),
+        _ => false,
+    }
+}
+
+/// True when the expression matches at any position, whatever the input.
+fn always_succeeds(rule: &Rule) -> bool {
+    match rule {
+        Rule::Empty | Rule::ZeroOrMore(_) | Rule::Optional(_) => true,
+        Rule::Literal(text) | Rule::InsensitiveLiteral(text) => text.is_empty(),
+        Rule::Group(expr) => always_succeeds(expr),
+        Rule::Sequence(sequence) => sequence.iter().all(always_succeeds),
+        Rule::Choice(choices) => choices.iter().any(always_succeeds),
+        _ => false,
+    }
+}
+
+/// Text that input must start with for the expression to match (possibly empty).
+fn required_prefix(rule: &Rule) -> String {
+    match rule {
+        Rule::Literal(text) => text.clone(),
+        Rule::Group(expr) | Rule::OneOrMore(expr) => required_prefix(expr),
+        Rule::Sequence(sequence) => {
+            let mut prefix = String::new();
+            for item in sequence {
+                match item {
+                    Rule::Literal(text) => prefix.push_str(text),
+                    _ => {
+                        prefix.push_str(&required_prefix(item));
+                        break;
+                    }
+                }
+            }
+            prefix
+        }
+        _ => String::new(),
+    }
+}
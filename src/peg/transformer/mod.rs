@@ -1,5 +1,9 @@
 mod core;
 mod errors;
+mod lint;
+mod types;
 
 pub use self::core::Transformer;
 pub use self::errors::TransformError;
+pub use self::lint::{LintKind, LintWarning};
+pub(crate) use self::types::Fragment;
@@ -0,0 +1,15 @@
+use crate::peg::parsing::Span;
+use crate::peg::rule::{Annotation, Rule};
+use std::collections::HashMap;
+
+/// Rules read from one or more grammar texts, before the undefined-rule check and compilation.
+/// Each rule keeps the names it refers to, with their spans, so the check can run once
+/// fragments are combined.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Fragment {
+    pub rules: HashMap<String, (Rule, Vec<(String, Span)>)>,
+    /// Where each rule's name is defined
+    pub spans: HashMap<String, Span>,
+    pub docs: HashMap<String, String>,
+    pub annotations: HashMap<String, Annotation>,
+}
@@ -0,0 +1,115 @@
+use super::errors::SelectorError;
+use super::types::{Cursor, PostOrder, PreOrder, Selector};
+use crate::peg::parsing::{ParseNode, Span};
+
+impl ParseNode {
+    /// The rule that produced this node, `None` for terminals.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            ParseNode::NonTerminal { name, .. } => Some(name),
+            ParseNode::Terminal { .. } => None,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            ParseNode::Terminal { span } | ParseNode::NonTerminal { span, .. } => *span,
+        }
+    }
+
+    pub fn children(&self) -> &[ParseNode] {
+        match self {
+            ParseNode::NonTerminal { children, .. } => children,
+            ParseNode::Terminal { .. } => &[],
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, ParseNode::Terminal { .. })
+    }
+
+    /// The part of `input` this node covers; `input` must be the text that was parsed.
+    pub fn text<'i>(&self, input: &'i str) -> &'i str {
+        let Span(start, end) = self.span();
+        &input[start..end]
+    }
+
+    /// This node and everything below it, parents before children.
+    pub fn pre_order(&self) -> PreOrder<'_> {
+        PreOrder { stack: vec![self] }
+    }
+
+    /// This node and everything below it, children before parents.
+    pub fn post_order(&self) -> PostOrder<'_> {
+        PostOrder {
+            stack: vec![(self, false)],
+        }
+    }
+
+    /// Everything below this node in pre-order, without the node itself.
+    pub fn descendants(&self) -> PreOrder<'_> {
+        PreOrder {
+            stack: self.children().iter().rev().collect(),
+        }
+    }
+
+    /// The first node in pre-order (possibly this one) produced by rule `name`.
+    pub fn find(&self, name: &str) -> Option<&ParseNode> {
+        self.pre_order().find(|node| node.name() == Some(name))
+    }
+
+    /// All nodes in pre-order (possibly including this one) produced by rule `name`.
+    pub fn find_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a ParseNode> + 'a {
+        self.pre_order()
+            .filter(move |node| node.name() == Some(name))
+    }
+
+    /// A cursor on this node, which acts as the root: the cursor cannot move above it.
+    pub fn cursor(&self) -> Cursor<'_> {
+        Cursor {
+            path: vec![(self, 0)],
+        }
+    }
+
+    /// A cursor on `target`, which must be this node or one of its descendants (compared by
+    /// address, e.g. a node returned by [`find`](Self::find)), with this node as the root.
+    pub fn cursor_at<'a>(&'a self, target: &ParseNode) -> Option<Cursor<'a>> {
+        let mut cursor = self.cursor();
+        if cursor.seek(target) {
+            Some(cursor)
+        } else {
+            None
+        }
+    }
+
+    /// Nodes matching `selector` (see [`Selector`]), in pre-order.
+    pub fn select(&self, selector: &str) -> Result<Vec<&ParseNode>, SelectorError> {
+        Ok(Selector::parse(selector)?.select(self))
+    }
+}
+
+impl<'a> Iterator for PreOrder<'a> {
+    type Item = &'a ParseNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children().iter().rev());
+        Some(node)
+    }
+}
+
+impl<'a> Iterator for PostOrder<'a> {
+    type Item = &'a ParseNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+            if expanded || node.children().is_empty() {
+                return Some(node);
+            }
+            self.stack.push((node, true));
+            self.stack
+                .extend(node.children().iter().rev().map(|child| (child, false)));
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use super::types::Cursor;
+use crate::peg::parsing::{ParseNode, Span};
+
+impl<'a> Cursor<'a> {
+    pub fn node(&self) -> &'a ParseNode {
+        self.current().0
+    }
+
+    /// Number of moves from the root down to the current node.
+    pub fn depth(&self) -> usize {
+        self.path.len() - 1
+    }
+
+    pub fn parent(&self) -> Option<&'a ParseNode> {
+        self.path.len().checked_sub(2).map(|i| self.path[i].0)
+    }
+
+    /// The current node's ancestors up to the root, innermost first.
+    pub fn ancestors(&self) -> impl Iterator<Item = &'a ParseNode> + '_ {
+        self.path.iter().rev().skip(1).map(|(node, _)| *node)
+    }
+
+    pub fn goto_parent(&mut self) -> bool {
+        if self.path.len() > 1 {
+            self.path.pop();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn goto_first_child(&mut self) -> bool {
+        match self.node().children().first() {
+            Some(child) => {
+                self.path.push((child, 0));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn goto_last_child(&mut self) -> bool {
+        let children = self.node().children();
+        match children.last() {
+            Some(child) => {
+                self.path.push((child, children.len() - 1));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn goto_next_sibling(&mut self) -> bool {
+        self.goto_sibling(|index| index.checked_add(1))
+    }
+
+    pub fn goto_prev_sibling(&mut self) -> bool {
+        self.goto_sibling(|index| index.checked_sub(1))
+    }
+
+    fn goto_sibling(&mut self, step: impl Fn(usize) -> Option<usize>) -> bool {
+        let Some(parent) = self.parent() else {
+            return false;
+        };
+        let (_, index) = self.current();
+        match step(index).and_then(|i| parent.children().get(i).map(|node| (node, i))) {
+            Some(sibling) => {
+                *self.path.last_mut().expect("cursor path is never empty") = sibling;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn current(&self) -> (&'a ParseNode, usize) {
+        *self.path.last().expect("cursor path is never empty")
+    }
+
+    /// Moves down from the current node to `target`; leaves the cursor unchanged if `target`
+    /// is not below it.
+    pub(super) fn seek(&mut self, target: &ParseNode) -> bool {
+        if std::ptr::eq(self.node(), target) {
+            return true;
+        }
+        let Span(start, end) = target.span();
+        for (index, child) in self.node().children().iter().enumerate() {
+            let span = child.span();
+            if span.0 > start || span.1 < end {
+                continue;
+            }
+            self.path.push((child, index));
+            if self.seek(target) {
+                return true;
+            }
+            self.path.pop();
+        }
+        false
+    }
+}
@@ -0,0 +1,26 @@
+use std::fmt::{Display, Formatter};
+
+/// Error types for selector parsing; positions are byte offsets into the selector text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SelectorError {
+    /// Error when the selector, or one of its `,` alternatives, is empty
+    Empty,
+    /// Error when a character cannot start or continue a selector
+    UnexpectedChar { found: char, position: usize },
+    /// Error when `>` is not between two rule names
+    MissingName { position: usize },
+}
+
+impl Display for SelectorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelectorError::Empty => write!(f, "Empty selector"),
+            SelectorError::UnexpectedChar { found, position } => {
+                write!(f, "Unexpected {:?} at {} in selector", found, position)
+            }
+            SelectorError::MissingName { position } => {
+                write!(f, "Expected a rule name at {} in selector", position)
+            }
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Navigating parse trees: pre/post-order iterators, lookup by rule name, a cursor with
+//! parent and sibling moves, and CSS-like selectors over rule names.
+//!
+//! ```rust
+//! use camxes_rs::peg::grammar::Peg;
+//!
+//! let peg = Peg::new("sum", "sum <- num ('+' num)*\nnum <- [0-9]+").unwrap();
+//! let input = "1+20+3";
+//! let result = peg.parse(input);
+//! let root = result.root().unwrap();
+//!
+//! let numbers: Vec<_> = root.find_all("num").map(|n| n.text(input)).collect();
+//! assert_eq!(numbers, ["1", "20", "3"]);
+//!
+//! let selected = root.select("sum > num").unwrap();
+//! assert_eq!(selected.len(), 3);
+//!
+//! let mut cursor = root.cursor_at(selected[1]).unwrap();
+//! assert!(cursor.goto_next_sibling());
+//! assert_eq!(cursor.node().text(input), "+");
+//! ```
+
+mod core;
+mod cursor;
+mod errors;
+mod selector;
+mod types;
+
+pub use self::errors::SelectorError;
+pub use self::types::{Cursor, PostOrder, PreOrder, Selector};
@@ -0,0 +1,174 @@
+use super::errors::SelectorError;
+use super::types::{Combinator, NameTest, Selector, Step};
+use crate::peg::parsing::ParseNode;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+impl Selector {
+    pub fn parse(text: &str) -> Result<Self, SelectorError> {
+        let mut alternatives = vec![];
+        let mut offset = 0;
+        for part in text.split(',') {
+            alternatives.push(Self::parse_steps(part, offset)?);
+            offset += part.len() + 1;
+        }
+        Ok(Self { alternatives })
+    }
+
+    fn parse_steps(text: &str, offset: usize) -> Result<Vec<Step>, SelectorError> {
+        let mut steps: Vec<Step> = vec![];
+        let mut pending = None;
+        let mut chars = text.char_indices().peekable();
+        while let Some(&(i, c)) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+                if !steps.is_empty() && pending.is_none() {
+                    pending = Some(Combinator::Descendant);
+                }
+            } else if c == '>' {
+                chars.next();
+                if steps.is_empty() || pending == Some(Combinator::Child) {
+                    return Err(SelectorError::MissingName {
+                        position: offset + i,
+                    });
+                }
+                pending = Some(Combinator::Child);
+            } else if c == '*' || is_name_char(c) {
+                if !steps.is_empty() && pending.is_none() {
+                    return Err(SelectorError::UnexpectedChar {
+                        found: c,
+                        position: offset + i,
+                    });
+                }
+                let test = if c == '*' {
+                    chars.next();
+                    NameTest::Any
+                } else {
+                    let mut name = String::new();
+                    while let Some(&(_, c)) = chars.peek().filter(|(_, c)| is_name_char(*c)) {
+                        name.push(c);
+                        chars.next();
+                    }
+                    NameTest::Name(name)
+                };
+                steps.push(Step {
+                    combinator: pending.take().unwrap_or(Combinator::Descendant),
+                    test,
+                });
+            } else {
+                return Err(SelectorError::UnexpectedChar {
+                    found: c,
+                    position: offset + i,
+                });
+            }
+        }
+        if pending == Some(Combinator::Child) {
+            return Err(SelectorError::MissingName {
+                position: offset + text.len(),
+            });
+        }
+        if steps.is_empty() {
+            return Err(SelectorError::Empty);
+        }
+        Ok(steps)
+    }
+
+    /// True when `node`, whose ancestors from the root down are `ancestors`, matches.
+    pub fn matches(&self, node: &ParseNode, ancestors: &[&ParseNode]) -> bool {
+        self.alternatives
+            .iter()
+            .any(|steps| matches_steps(steps, node, ancestors))
+    }
+
+    /// Nodes under (and including) `root` that match, in pre-order.
+    pub fn select<'a>(&self, root: &'a ParseNode) -> Vec<&'a ParseNode> {
+        fn walk<'a>(
+            selector: &Selector,
+            node: &'a ParseNode,
+            ancestors: &mut Vec<&'a ParseNode>,
+            found: &mut Vec<&'a ParseNode>,
+        ) {
+            if node.is_terminal() {
+                return;
+            }
+            if selector.matches(node, ancestors) {
+                found.push(node);
+            }
+            ancestors.push(node);
+            for child in node.children() {
+                walk(selector, child, ancestors, found);
+            }
+            ancestors.pop();
+        }
+
+        let mut found = vec![];
+        walk(self, root, &mut vec![], &mut found);
+        found
+    }
+}
+
+fn matches_steps(steps: &[Step], node: &ParseNode, ancestors: &[&ParseNode]) -> bool {
+    let Some((last, rest)) = steps.split_last() else {
+        return true;
+    };
+    if !last.test.matches(node) {
+        return false;
+    }
+    if rest.is_empty() {
+        return true;
+    }
+    match last.combinator {
+        Combinator::Child => ancestors
+            .split_last()
+            .is_some_and(|(parent, above)| matches_steps(rest, parent, above)),
+        Combinator::Descendant => {
+            (0..ancestors.len()).any(|i| matches_steps(rest, ancestors[i], &ancestors[..i]))
+        }
+    }
+}
+
+impl NameTest {
+    fn matches(&self, node: &ParseNode) -> bool {
+        match (self, node.name()) {
+            (NameTest::Any, Some(_)) => true,
+            (NameTest::Name(expected), Some(name)) => expected == name,
+            (_, None) => false,
+        }
+    }
+}
+
+/// Rule names may be qualified with an import namespace (`morph.Vowel`).
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.'
+}
+
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::parse(text)
+    }
+}
+
+impl Display for Selector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, steps) in self.alternatives.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            for (j, step) in steps.iter().enumerate() {
+                if j > 0 {
+                    match step.combinator {
+                        Combinator::Child => write!(f, " > ")?,
+                        Combinator::Descendant => write!(f, " ")?,
+                    }
+                }
+                match &step.test {
+                    NameTest::Any => write!(f, "*")?,
+                    NameTest::Name(name) => write!(f, "{}", name)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
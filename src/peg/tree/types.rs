@@ -0,0 +1,49 @@
+use crate::peg::parsing::ParseNode;
+
+/// Depth-first iterator yielding each node before its children.
+#[derive(Clone, Debug)]
+pub struct PreOrder<'a> {
+    pub(super) stack: Vec<&'a ParseNode>,
+}
+
+/// Depth-first iterator yielding each node after its children.
+#[derive(Clone, Debug)]
+pub struct PostOrder<'a> {
+    /// Nodes still to visit, and whether their children have been pushed already.
+    pub(super) stack: Vec<(&'a ParseNode, bool)>,
+}
+
+/// A position in a parse tree that can move to the parent, children and siblings of the
+/// current node. Created by [`ParseNode::cursor`] or [`ParseNode::cursor_at`].
+#[derive(Clone, Debug)]
+pub struct Cursor<'a> {
+    /// Path from the root to the current node, with each node's index among its siblings.
+    pub(super) path: Vec<(&'a ParseNode, usize)>,
+}
+
+/// A compiled selector such as `sentence > predicate predunit`: rule names (or `*` for any
+/// rule) joined by `>` (direct child) or whitespace (any descendant), with `,` separating
+/// alternatives.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selector {
+    pub(super) alternatives: Vec<Vec<Step>>,
+}
+
+/// One compound of a selector and how it relates to the previous one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct Step {
+    pub combinator: Combinator,
+    pub test: NameTest,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum NameTest {
+    Any,
+    Name(String),
+}